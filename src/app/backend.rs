@@ -1,11 +1,11 @@
-use crate::app::{App, AngleSet, Mode};
-use crate::driver::DriverError;
+use crate::app::{App, AngleSet, Mode, DriverView};
+use crate::app::command::{BufferCommand, Position, parse_position, parse_motor};
+use crate::driver::{Driver, DriverError, ArcShape, Joint};
+use crate::gcode::Interpreter;
 
 use crate::driver;
 use crate::utils::{Utils, ShiftingVec};
 
-use std::num::{ParseFloatError, ParseIntError};
-
 use rand::Rng;
 
 
@@ -28,101 +28,155 @@ impl App {
     }
 
     pub fn add_current_position(&mut self) {
-        let beam_angle = self.driver.beam_angle;
-        let column_angle = self.driver.column_angle;
-
-        let current_position = AngleSet {beam_angle, column_angle, rotation_angle: 0.0 };
+        let current_position = self.get_current_angle_set();
 
         self.prev_positions.insert(current_position);
     }
 
     //-------- MOVES --------\\
 
+    // moves run on the executor and report back through poll_jobs, so none of these wait on the motors
+
     pub fn move_direction(&mut self, dir: driver::Direction) {
-        match self.driver.move_direction(dir) {
-            Ok(()) => (), 
-            Err(e) => { self.handle_driver_error_generic(e) }
-        }
+        self.submit(move |driver| driver.move_direction(dir).map(|_| String::new()));
     }
 
     pub fn goto_smooth(&mut self) {
-        let (x, y) = match self.parse_buffer_goto() {
+        match parse_position(&self.buffer) {
+            Ok(x) if x.z.is_some() => self.command_output.insert(String::from("smooth moves stay in the arm's plane, leave out z")),
+            Ok(x) => self.execute_command(BufferCommand::Smooth(x.x, x.y, x.policy, x.profile)),
+            Err(e) => self.command_output.insert(e.get_message())
+        }
+    }
+
+    // with a z the line is in the world and the base turns along it
+    pub fn goto_line(&mut self) {
+        let position = match parse_position(&self.buffer) {
             Ok(x) => x,
             Err(e) => {
-                self.command_output.insert(format!("{}", e));
+                self.command_output.insert(e.get_message());
                 return
             }
         };
 
         self.add_current_position();
-        
-        match self.driver.goto_point_smooth(x, y) {
-            Ok(()) => (),
-            Err(e) => { self.handle_driver_error_generic(e) }
-        }
+
+        let (x, y) = (position.x, position.y);
+
+        self.submit(move |driver| {
+            let result = match position.z {
+                Some(z) => driver.goto_line_3d(x, y, z, driver.tool_speed),
+                None => driver.goto_line(x, y)
+            };
+
+            result.map(|_| format!("moved in a straight line to {}", App::format_position(&position)))
+        });
     }
 
-    pub fn goto(&mut self) {
-        let (x, y) = match self.parse_buffer_goto() {
+    pub fn goto_arc(&mut self) {
+        let (x, y, shape, clockwise) = match self.parse_buffer_arc() {
             Ok(x) => x,
             Err(e) => {
-                self.command_output.insert(format!("{}", e));
+                self.command_output.insert(e);
                 return
             }
         };
 
-        self.command_output.insert(format!("successfully parsed buffer"));
-
-        let current_poistion = self.get_current_position();
+        self.add_current_position();
 
-        match self.driver.goto_point(x, y) {
-            Ok(()) => {
-                self.command_output.insert(format!("successfully wennt to point {} {}", x, y));
-                self.prev_positions.insert(current_poistion);
-            },
+        self.submit(move |driver| driver.goto_arc(x, y, shape, clockwise).map(|_| format!("moved along an arc to {} {}", x, y)));
+    }
 
-            Err(e) => { self.handle_driver_error_generic(e) }
+    // the whole program runs as one job
+    pub fn run_gcode(&mut self, path: String) {
+        if path.is_empty() {
+            self.command_output.insert(String::from("put the path of a g-code file in the buffer"));
+            return
         }
-    }
 
-    pub fn move_motor(&mut self) {
-        let (motor, steps) = match self.parse_move_motor() {
+        let program = match Interpreter::load(&path) {
             Ok(x) => x,
             Err(e) => {
-                self.command_output.insert(format!("{}", e));
-                return;
+                self.command_output.insert(e.get_message());
+                return
             }
         };
 
-        if motor == "beam" {
-            self.driver.move_beam(steps);
-        } else {
-            self.driver.move_column(steps);
-        } 
+        self.add_current_position();
+
+        self.executor.submit(Box::new(move |driver| {
+            match Interpreter::new(driver).run(driver, &program) {
+                Ok(blocks) => Ok(format!("ran {} blocks from {}", blocks, path)),
+                Err(e) => Err(e.get_message())
+            }
+        }));
     }
 
-    pub fn move_beam_pos(&mut self) {
-        let step_amount = self.driver.generic_step_amount;
+    pub fn goto(&mut self) {
+        match parse_position(&self.buffer) {
+            Ok(x) => match x.z {
+                Some(z) => self.execute_command(BufferCommand::Goto3d(x.x, x.y, z, x.policy)),
+                None => self.execute_command(BufferCommand::Goto(x.x, x.y, x.policy))
+            },
 
-        self.driver.move_beam(step_amount);
+            Err(e) => self.command_output.insert(e.get_message())
+        }
     }
 
-    pub fn move_beam_neg(&mut self) {
-        let step_amount = self.driver.generic_step_amount * -1;
+    pub fn move_motor(&mut self) {
+        match parse_motor(&self.buffer) {
+            Ok((joint, amount)) => self.execute_command(BufferCommand::Motor(joint, amount)),
+            Err(e) => self.command_output.insert(e.get_message())
+        }
+    }
 
-        self.driver.move_beam(step_amount);
+    pub fn move_beam_pos(&mut self) {
+        self.submit(|driver| driver.move_joint(Joint::Beam, driver.generic_step_amount).map(|_| String::new()));
     }
 
-    pub fn move_column_pos(&mut self) {
-        let step_amount = self.driver.generic_step_amount;
+    pub fn move_beam_neg(&mut self) {
+        self.submit(|driver| driver.move_joint(Joint::Beam, -driver.generic_step_amount).map(|_| String::new()));
+    }
 
-        self.driver.move_column(step_amount);
+    pub fn move_column_pos(&mut self) {
+        self.submit(|driver| driver.move_joint(Joint::Column, driver.generic_step_amount).map(|_| String::new()));
     }
 
     pub fn move_column_neg(&mut self) {
-        let step_amount = self.driver.generic_step_amount * -1;
+        self.submit(|driver| driver.move_joint(Joint::Column, -driver.generic_step_amount).map(|_| String::new()));
+    }
+
+    pub fn move_base_pos(&mut self) {
+        self.submit(|driver| driver.move_joint(Joint::Base, driver.generic_step_amount).map(|_| String::new()));
+    }
+
+    pub fn move_base_neg(&mut self) {
+        self.submit(|driver| driver.move_joint(Joint::Base, -driver.generic_step_amount).map(|_| String::new()));
+    }
+
+    pub fn home(&mut self) {
+        // without switches homing only takes the counters as right again, which is still needed after a stop
+        if !self.view.has_home_switches && self.view.homed {
+            self.command_output.insert(String::from("no home switches configured, nothing to home"));
+            return
+        }
 
-        self.driver.move_column(step_amount);
+        self.submit(|driver| driver.home().map(|_| String::from("homed all axes")));
+    }
+
+    // brakes to a stop at the configured acceleration and drops anything queued
+    pub fn cancel_motion(&mut self) {
+        if !self.executor.is_busy() { return }
+
+        self.executor.cancel();
+        self.command_output.insert(String::from("cancelling, braking to a stop"));
+    }
+
+    pub fn emergency_stop(&mut self) {
+        self.executor.emergency_stop();
+        self.replay = None;
+        self.replay_job = None;
+        self.command_output.insert(String::from("EMERGENCY STOP, home before moving again"));
     }
 
     //-------- END MOVES --------\\
@@ -132,17 +186,11 @@ impl App {
     //-------- GETS --------\\ 
 
 
-    pub fn get_current_position(&mut self) -> AngleSet {
-        let beam_angle = self.driver.beam_angle;
-        let column_angle = self.driver.column_angle;
-
-        return AngleSet {beam_angle, column_angle, rotation_angle: 0.0 };
-    }
-    
     pub fn get_current_mode_string(&self) -> &str {
         let string = match self.current_mode {
             Mode::Normal => { "Normal" },
             Mode::Control => { "Control" },
+            Mode::Teach => { "Teach" },
             Mode::Buffer => { "Buffer" }
         };
 
@@ -150,48 +198,63 @@ impl App {
     } 
 
     pub fn get_current_x(&self) -> f32 {
-        return self.driver.current_position.x
+        return self.view.position.x
     }
 
     pub fn get_current_y(&self) -> f32 {
-        return self.driver.current_position.y
+        return self.view.position.y
     }
 
     pub fn get_current_z(&self) -> f32 {
-        return self.driver.current_position.z
+        return self.view.position.z
     }
 
     pub fn get_current_column_angle(&self) -> f32 {
-        return self.driver.column_angle;
+        return self.view.column_angle;
     }
 
     pub fn get_current_beam_angle(&self) -> f32 {
-        return self.driver.beam_angle;
+        return self.view.beam_angle;
     }
 
     pub fn get_current_base_angle(&self) -> f32 {
-        return self.driver.base_angle;
+        return self.view.base_angle;
     }
 
 
+    pub fn format_position(position: &Position) -> String {
+        return match position.z {
+            Some(z) => format!("{} {} {}", position.x, position.y, z),
+            None => format!("{} {}", position.x, position.y)
+        }
+    }
+
+    pub fn get_motion_status(&self) -> String {
+        return match self.executor.get_progress() {
+            Some((_, 0)) => String::from("busy"),
+            Some((done, total)) => format!("{}/{} ({}%)", done, total, done * 100 / total),
+            None => String::from("idle")
+        }
+    }
+
     pub fn get_current_angle_set(&self) ->  AngleSet {
         let beam_angle = self.get_current_beam_angle();
         let column_angle = self.get_current_column_angle();
+        let rotation_angle = self.get_current_base_angle();
 
-        return AngleSet { column_angle, beam_angle, rotation_angle: 0.0 }
+        return AngleSet { column_angle, beam_angle, rotation_angle }
     }
 
     pub fn get_2d_points(&self) -> Vec<(f64, f64)>{
-        let column = self.driver.get_column_position();
+        let pose = &self.view.pose;
 
-        let beam = self.driver.get_beam_position();
-
-        return vec![(0.0,0.0), (column.x as f64, column.y as f64), (beam.x as f64, beam.y as f64)]
+        return vec![(pose.base.x as f64, pose.base.y as f64), (pose.elbow.x as f64, pose.elbow.y as f64),
+                    (pose.end_effector.x as f64, pose.end_effector.y as f64)]
     }
 
     //this function may not need to exist
     pub fn get_x_z_points(&self) -> Vec<(f64, f64)> {
-        let return_vec = vec![(0.0, 0.0), (self.driver.current_position.x as f64, self.driver.current_position.z as f64)];
+        let return_vec = vec![(0.0, 0.0), (self.view.position.x as f64, self.view.position.z as f64)];
 
         return return_vec
     }
@@ -211,66 +274,110 @@ impl App {
     }
 
 
+    pub fn save_step_traces(&mut self) {
+        let recorders = match self.with_driver(|driver| {
+            driver.get_recorders().into_iter().map(|(name, recorder)| (name.to_string(), recorder)).collect::<Vec<_>>()
+        }) {
+            Some(x) => x,
+            None => return
+        };
+
+        if recorders.is_empty() {
+            self.command_output.insert(String::from("no step traces, motors are not simulated"));
+            return
+        }
+
+        for (name, recorder) in recorders {
+            let csv = recorder.lock().unwrap().to_csv();
+
+            match Utils::save_to_file(format!("./trace_{}.csv", name), csv) {
+                Ok(x) => self.command_output.insert(x),
+                Err(e) => self.command_output.insert(format!("unable to save trace: {}", e))
+            }
+        }
+    }
+
+
     //-------- STATE CHANGES --------\\
 
     pub fn increase_movement_amount(&mut self) {
-        self.driver.movement_amount *= 1.25;
+        self.with_driver(|driver| driver.movement_amount *= 1.25);
     }
 
     pub fn decrease_movement_amount(&mut self) {
-        self.driver.movement_amount /= 1.25;
+        self.with_driver(|driver| driver.movement_amount /= 1.25);
     }
 
     pub fn increase_max_delay(&mut self) {
-        self.driver.micro_delay_max += 10;
+        self.with_driver(|driver| driver.micro_delay_max += 10);
     }
 
     pub fn decrease_max_delay(&mut self) {
-        if self.driver.micro_delay_max <= 10 || self.driver.micro_delay_max <= self.driver.micro_delay_min + 10 {
-            return
-        }
+        self.with_driver(|driver| {
+            if driver.micro_delay_max <= driver.micro_delay_limit + 10 || driver.micro_delay_max <= driver.micro_delay_min + 10 {
+                return
+            }
 
-        self.driver.micro_delay_max -= 10;
+            driver.micro_delay_max -= 10;
+        });
     }
 
     pub fn increase_min_delay(&mut self) {
-        if self.driver.micro_delay_min >= self.driver.micro_delay_max - 10 {
-            return
-        }
+        self.with_driver(|driver| {
+            if driver.micro_delay_min >= driver.micro_delay_max - 10 {
+                return
+            }
 
-        self.driver.micro_delay_min += 10;
+            driver.micro_delay_min += 10;
+        });
     }
 
     pub fn decrease_min_delay(&mut self) {
-        if self.driver.micro_delay_min <= 10 {
-            return
-        }
+        self.with_driver(|driver| {
+            if driver.micro_delay_min <= driver.micro_delay_limit + 10 {
+                return
+            }
 
-        self.driver.micro_delay_min -= 10;
+            driver.micro_delay_min -= 10;
+        });
     }
 
     pub fn increase_delay(&mut self) {
-        self.driver.micro_delay_default += 10;
+        self.with_driver(|driver| driver.micro_delay_default += 10);
     }
 
     pub fn decrease_delay(&mut self) {
-        if self.driver.micro_delay_default <= 10 {
-            return
-        }
+        self.with_driver(|driver| {
+            if driver.micro_delay_default <= driver.micro_delay_limit + 10 {
+                return
+            }
+
+            driver.micro_delay_default -= 10;
+        });
+    }
+
+    pub fn toggle_coordinated(&mut self) {
+        let coordinated = match self.with_driver(|driver| { driver.coordinated = !driver.coordinated; driver.coordinated }) {
+            Some(x) => x,
+            None => return
+        };
 
-        self.driver.micro_delay_default -= 10;
+        let state = if coordinated { "on" } else { "off" };
+        self.command_output.insert(format!("coordinated moves {}", state));
     }
 
     pub fn increase_generic_step_amount(&mut self) {
-        self.driver.generic_step_amount += 50;
+        self.with_driver(|driver| driver.generic_step_amount += 50);
     }
 
     pub fn decrease_generic_step_amount(&mut self) {
-        if self.driver.generic_step_amount <= 50 {
-            return
-        }
+        self.with_driver(|driver| {
+            if driver.generic_step_amount <= 50 {
+                return
+            }
 
-        self.driver.generic_step_amount -= 50;
+            driver.generic_step_amount -= 50;
+        });
     }
 
     pub fn increase_prev_points(&mut self) {
@@ -323,46 +430,84 @@ impl App {
 
     //-------- MISC? --------\\
 
-    pub fn handle_driver_error_generic(&mut self, error: DriverError) {
-        match error {
-            DriverError::UnReachable => {
-                let error_message = String::from("unable to reach target position, out of range");
-                self.command_output.insert(error_message);
-            },
-            
-            DriverError::CantNormalize => {
-                let error_message = String::from("unable to normalize derived smooth, most likely a divide by zero issue");
-                self.command_output.insert(error_message);
+    pub fn get_error_message(error: &DriverError) -> String {
+        return match error {
+            DriverError::NotHomed | DriverError::Stopped => format!("{}, press h in normal mode", error.get_message()),
+            _ => error.get_message()
+        }
+    }
+
+    // queues a driver call, an empty message means there's nothing worth saying when it works
+    pub fn submit<F>(&mut self, job: F) -> u64 where F: FnOnce(&mut Driver) -> Result<String, DriverError> + Send + 'static {
+        return self.executor.submit(Box::new(move |driver| job(driver).map_err(|e| App::get_error_message(&e))))
+    }
+
+    // for changes that don't move anything, refused while a job has the driver
+    pub fn with_driver<T, F>(&mut self, f: F) -> Option<T> where F: FnOnce(&mut Driver) -> T {
+        let result = self.executor.try_driver().map(|mut driver| f(&mut driver));
+
+        if result.is_none() {
+            self.command_output.insert(String::from("busy moving, cancel with space first"));
+        }
+
+        return result
+    }
+
+    // messages from finished jobs and the driver's state for drawing, once a frame
+    pub fn poll_jobs(&mut self) {
+        for job in self.executor.poll() {
+            if Some(job.id) == self.replay_job {
+                self.finish_replay_step(job.result);
+                continue
             }
+
+            match job.result {
+                Ok(x) if x.is_empty() => (),
+                Ok(x) => self.command_output.insert(x),
+                Err(e) => self.command_output.insert(e)
+            }
+        }
+
+        if let Some(driver) = self.executor.try_driver() {
+            self.view = DriverView::new(&driver);
         }
     }
 
-    pub fn parse_buffer_goto(&self) -> Result<(f32, f32), ParseFloatError> {
-        let coords = self.buffer.split(" ").collect::<Vec<&str>>();
+    // x y, then center cx cy, radius r or through vx vy, then cw or ccw. through picks its own direction
+    pub fn parse_buffer_arc(&self) -> Result<(f32, f32, ArcShape, bool), String> {
+        let words = self.buffer.split_whitespace().collect::<Vec<&str>>();
+        let usage = String::from("arcs are written x y center cx cy, x y radius r or x y through vx vy, then cw or ccw");
 
-        let x = match coords[0].parse::<f32>() {
-            Ok(x) => x,
-            Err(e) => return Err(e)
-        };
+        let numbers = |range: std::ops::Range<usize>| -> Result<Vec<f32>, String> {
+            let mut numbers = Vec::new();
+
+            for i in range {
+                match words.get(i).map(|x| x.parse::<f32>()) {
+                    Some(Ok(x)) => numbers.push(x),
+                    Some(Err(e)) => return Err(format!("{}: {}", words[i], e)),
+                    None => return Err(usage.clone())
+                }
+            }
 
-        let y = match coords[1].parse::<f32>() {
-            Ok(y) => y,
-            Err(e) => return Err(e)
+            Ok(numbers)
         };
 
-        Ok((x, y))
-    }
+        let end = numbers(0..2)?;
 
-    pub fn parse_move_motor(&mut self) -> Result<(&str, i32), ParseIntError> {
-        let args = self.buffer.split(" ").collect::<Vec<&str>>();
-        let motor = args[0];
+        let (shape, next) = match words.get(2) {
+            Some(&"center") => { let x = numbers(3..5)?; (ArcShape::Center(x[0], x[1]), 5) },
+            Some(&"radius") => { let x = numbers(3..4)?; (ArcShape::Radius(x[0]), 4) },
+            Some(&"through") => { let x = numbers(3..5)?; (ArcShape::Through(x[0], x[1]), 5) },
+            _ => return Err(usage)
+        };
 
-        let steps = match args[1].parse::<i32>() {
-            Ok(x) => x,
-            Err(e) => return Err(e)
+        let clockwise = match words.get(next) {
+            Some(&"cw") => true,
+            Some(&"ccw") | None => false,
+            Some(x) => return Err(format!("unknown direction {}, use cw or ccw", x))
         };
 
-        Ok((motor, steps))
+        Ok((end[0], end[1], shape, clockwise))
     }
 
     //-------- END MISC ------\\
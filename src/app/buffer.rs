@@ -0,0 +1,113 @@
+use crate::app::{App, Mode};
+use crate::app::command::{get_completions, get_common_prefix};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// editing the buffer like a shell prompt. the cursor counts characters, not bytes
+impl App {
+    pub fn handle_buffer_key(&mut self, key: KeyEvent) {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Esc => { self.current_mode = Mode::Normal; },
+
+            KeyCode::Enter => { self.run_buffer(); },
+
+            KeyCode::Char('u') if control => { self.clear_buffer(); },
+
+            KeyCode::Char('a') if control => { self.buffer_cursor = 0; },
+
+            KeyCode::Char('e') if control => { self.buffer_cursor = self.buffer.chars().count(); },
+
+            KeyCode::Char(_) if control => {},
+
+            KeyCode::Char(c) => { self.insert_buffer_char(c); },
+
+            KeyCode::Backspace => {
+                if self.get_buffer_cursor() == 0 { return }
+
+                self.buffer_cursor = self.get_buffer_cursor() - 1;
+                self.remove_buffer_char();
+            },
+
+            KeyCode::Delete => { self.remove_buffer_char(); },
+
+            KeyCode::Left => { self.buffer_cursor = self.get_buffer_cursor().saturating_sub(1); },
+
+            KeyCode::Right => { self.buffer_cursor = usize::min(self.get_buffer_cursor() + 1, self.buffer.chars().count()); },
+
+            KeyCode::Home => { self.buffer_cursor = 0; },
+
+            KeyCode::End => { self.buffer_cursor = self.buffer.chars().count(); },
+
+            KeyCode::Up => {
+                if let Some(x) = self.history.previous(&self.buffer) { self.set_buffer(x) }
+            },
+
+            KeyCode::Down => {
+                if let Some(x) = self.history.next() { self.set_buffer(x) }
+            },
+
+            KeyCode::Tab => { self.complete_buffer(); },
+
+            _ => {}
+        }
+    }
+
+    // anything can change the buffer out from under the cursor, so it's clamped whenever it's read
+    pub fn get_buffer_cursor(&self) -> usize {
+        return usize::min(self.buffer_cursor, self.buffer.chars().count())
+    }
+
+    pub fn set_buffer(&mut self, text: String) {
+        self.buffer_cursor = text.chars().count();
+        self.buffer = text;
+    }
+
+    pub fn clear_buffer(&mut self) {
+        self.set_buffer(String::new());
+    }
+
+    fn get_buffer_offset(&self) -> usize {
+        return self.buffer.char_indices().nth(self.get_buffer_cursor()).map(|(i, _)| i).unwrap_or(self.buffer.len())
+    }
+
+    fn insert_buffer_char(&mut self, c: char) {
+        let offset = self.get_buffer_offset();
+
+        self.buffer.insert(offset, c);
+        self.buffer_cursor = self.get_buffer_cursor() + 1;
+    }
+
+    // the one under the cursor
+    fn remove_buffer_char(&mut self) {
+        let offset = self.get_buffer_offset();
+
+        if offset < self.buffer.len() { self.buffer.remove(offset); }
+    }
+
+    // finishes the word before the cursor when only one thing fits, otherwise as far as they all agree and
+    // lists them
+    fn complete_buffer(&mut self) {
+        let offset = self.get_buffer_offset();
+        let names: Vec<String> = self.program.waypoints.iter().map(|x| x.name.clone()).collect();
+        let (start, matches) = get_completions(&self.buffer[..offset], &names);
+
+        let completion = match matches.len() {
+            0 => return,
+            1 => format!("{} ", matches[0]),
+            _ => {
+                self.command_output.insert(matches.join(" "));
+                get_common_prefix(&matches)
+            }
+        };
+
+        if completion.len() < offset - start { return }
+
+        let rest = self.buffer[offset..].to_string();
+        let text = format!("{}{}", &self.buffer[..start], completion);
+
+        self.buffer_cursor = text.chars().count();
+        self.buffer = format!("{}{}", text, rest);
+    }
+}
@@ -0,0 +1,725 @@
+use crate::app::{App, Mode};
+use crate::app::teach::DEFAULT_PROGRAM_PATH;
+use crate::driver::{Driver, ElbowPolicy, Joint};
+use crate::profile::ProfileKind;
+use crate::program::ProgramError;
+
+pub const VERBS: [&str; 10] = ["goto", "goto3d", "smooth", "plan", "motor", "set", "save", "load", "home", "run"];
+
+pub const SETTINGS: [&str; 11] = ["delay", "min-delay", "max-delay", "movement", "step", "elbow", "profile", "velocity",
+                                  "acceleration", "tool-speed", "coordinated"];
+
+// suffixes a number may be written with and what each multiplies it by, the first is used when there's none
+const DELAY_UNITS: [(&str, f32); 3] = [("us", 1.0), ("ms", 1000.0), ("s", 1_000_000.0)];
+const ANGLE_UNITS: [(&str, f32); 2] = [("deg", 1.0), ("rad", 180.0 / std::f32::consts::PI)];
+const VELOCITY_UNITS: [(&str, f32); 2] = [("deg/s", 1.0), ("rad/s", 180.0 / std::f32::consts::PI)];
+const ACCELERATION_UNITS: [(&str, f32); 2] = [("deg/s2", 1.0), ("rad/s2", 180.0 / std::f32::consts::PI)];
+const SPEED_UNITS: [(&str, f32); 2] = [("/s", 1.0), ("/min", 1.0 / 60.0)];
+const LENGTH_UNITS: [(&str, f32); 0] = [];
+
+#[derive(Debug, PartialEq)]
+pub enum CommandError {
+    Empty,
+    UnknownVerb(String),
+    Missing(String, String), // the verb and what it still needed
+    NotANumber(String),
+    UnknownUnit(String, String), // the word and the units it could have had
+    Invalid(String, String), // the word and what it should have been
+    Unexpected(String)
+}
+
+impl CommandError {
+    pub fn get_message(&self) -> String {
+        match self {
+            CommandError::Empty => String::from("the buffer is empty"),
+            CommandError::UnknownVerb(verb) => format!("unknown command '{}', use {}", verb, VERBS.join(", ")),
+            CommandError::Missing(verb, what) => format!("{} needs {}", verb, what),
+            CommandError::NotANumber(word) => format!("'{}' is not a number", word),
+            CommandError::UnknownUnit(word, units) if units.is_empty() => format!("'{}' can't have a unit", word),
+            CommandError::UnknownUnit(word, units) => format!("'{}' has an unknown unit, use {}", word, units),
+            CommandError::Invalid(word, what) => format!("'{}' is not {}", word, what),
+            CommandError::Unexpected(word) => format!("unexpected '{}' at the end", word)
+        }
+    }
+}
+
+// a target typed into the buffer, z turns the base out of the arm's plane
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+    pub z: Option<f32>,
+    pub policy: Option<ElbowPolicy>,
+    pub profile: Option<ProfileKind>
+}
+
+// how far to move a single joint, steps unless written as an angle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JointAmount {
+    Steps(i32),
+    Degrees(f32)
+}
+
+impl JointAmount {
+    pub fn get_steps(&self, driver: &Driver, joint: Joint) -> i32 {
+        match self {
+            JointAmount::Steps(x) => *x,
+            JointAmount::Degrees(x) => (x / driver.get_step_degree(joint)).round() as i32
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Setting {
+    Delay(i64),
+    MinDelay(i64),
+    MaxDelay(i64),
+    Movement(f32),
+    StepAmount(i32),
+    Elbow(ElbowPolicy),
+    Profile(ProfileKind),
+    Velocity(f32),
+    Acceleration(f32),
+    ToolSpeed(f32),
+    Coordinated(bool)
+}
+
+impl Setting {
+    // checked against the driver here rather than when parsing, the limits are part of its state
+    pub fn apply(&self, driver: &mut Driver) -> Result<String, String> {
+        let limit = driver.micro_delay_limit;
+        let too_short = |x: i64| -> Result<(), String> {
+            if x < limit { return Err(format!("{}us is below the delay limit of {}us", x, limit)) }
+            Ok(())
+        };
+
+        match *self {
+            Setting::Delay(x) => { too_short(x)?; driver.micro_delay_default = x; },
+
+            Setting::MinDelay(x) => {
+                too_short(x)?;
+                if x > driver.micro_delay_max { return Err(format!("min-delay can't be above max-delay {}us", driver.micro_delay_max)) }
+                driver.micro_delay_min = x;
+            },
+
+            Setting::MaxDelay(x) => {
+                too_short(x)?;
+                if x < driver.micro_delay_min { return Err(format!("max-delay can't be below min-delay {}us", driver.micro_delay_min)) }
+                driver.micro_delay_max = x;
+            },
+
+            Setting::Movement(x) => driver.movement_amount = x,
+            Setting::StepAmount(x) => driver.generic_step_amount = x,
+            Setting::Elbow(x) => driver.elbow_policy = x,
+            Setting::Profile(x) => driver.profile = x,
+            Setting::Velocity(x) => driver.max_velocity = x,
+            Setting::Acceleration(x) => driver.acceleration = x,
+            Setting::ToolSpeed(x) => driver.tool_speed = x,
+            Setting::Coordinated(x) => driver.coordinated = x
+        }
+
+        return Ok(format!("set {}", self.describe()))
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Setting::Delay(x) => format!("delay {}us", x),
+            Setting::MinDelay(x) => format!("min-delay {}us", x),
+            Setting::MaxDelay(x) => format!("max-delay {}us", x),
+            Setting::Movement(x) => format!("movement {}", x),
+            Setting::StepAmount(x) => format!("step {}", x),
+            Setting::Elbow(x) => format!("elbow {}", x.get_name()),
+            Setting::Profile(x) => format!("profile {}", x.get_name()),
+            Setting::Velocity(x) => format!("velocity {}deg/s", x),
+            Setting::Acceleration(x) => format!("acceleration {}deg/s2", x),
+            Setting::ToolSpeed(x) => format!("tool-speed {}/s", x),
+            Setting::Coordinated(x) => format!("coordinated {}", if *x { "on" } else { "off" })
+        }
+    }
+}
+
+// what can be typed into the buffer and run with enter
+#[derive(Clone, Debug, PartialEq)]
+pub enum BufferCommand {
+    Goto(f32, f32, Option<ElbowPolicy>),
+    GotoWaypoint(String),
+    Goto3d(f32, f32, f32, Option<ElbowPolicy>),
+    Smooth(f32, f32, Option<ElbowPolicy>, Option<ProfileKind>),
+    Plan(Position),
+    Motor(Joint, JointAmount),
+    Set(Setting),
+    Save(Option<String>),
+    Load(Option<String>),
+    Home,
+    Run(String)
+}
+
+impl BufferCommand {
+    // whether the buffer starts with a verb, anything else is left for the keys that read it
+    pub fn is_command(text: &str) -> bool {
+        return text.split_whitespace().next().is_some_and(|x| VERBS.contains(&x.to_lowercase().as_str()))
+    }
+
+    pub fn parse(text: &str) -> Result<BufferCommand, CommandError> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+
+        let verb = match words.first() {
+            Some(x) => x.to_lowercase(),
+            None => return Err(CommandError::Empty)
+        };
+
+        let mut args = Args::new(&verb, &words[1..]);
+
+        let command = match verb.as_str() {
+            // a lone name is a taught waypoint
+            "goto" if words.len() == 2 && split_unit(words[1]).0.is_empty() => BufferCommand::GotoWaypoint(args.word("x y")?.to_string()),
+
+            "goto" => {
+                let (x, y) = (args.length("x y")?, args.length("x y")?);
+                let z = args.optional_length()?;
+                let policy = args.optional(ElbowPolicy::parse, "an elbow (up, down, near, safe)")?;
+
+                match z {
+                    Some(z) => BufferCommand::Goto3d(x, y, z, policy),
+                    None => BufferCommand::Goto(x, y, policy)
+                }
+            },
+
+            "goto3d" => {
+                let (x, y, z) = (args.length("x y z")?, args.length("x y z")?, args.length("x y z")?);
+                let policy = args.optional(ElbowPolicy::parse, "an elbow (up, down, near, safe)")?;
+
+                BufferCommand::Goto3d(x, y, z, policy)
+            },
+
+            // smooth moves stay in the arm's plane, so a z is refused as an unknown option
+            "smooth" => {
+                let position = args.position(false)?;
+
+                BufferCommand::Smooth(position.x, position.y, position.policy, position.profile)
+            },
+
+            // what a goto there would do, without a profile it's timed like goto and with one like smooth
+            "plan" => BufferCommand::Plan(args.position(true)?),
+
+            "motor" => BufferCommand::Motor(args.joint()?, args.joint_amount()?),
+
+            "set" => BufferCommand::Set(args.setting()?),
+
+            "save" => BufferCommand::Save(args.rest()),
+
+            "load" => BufferCommand::Load(args.rest()),
+
+            "home" => BufferCommand::Home,
+
+            "run" => match args.rest() {
+                Some(x) => BufferCommand::Run(x),
+                None => return Err(CommandError::Missing(verb, String::from("a g-code file")))
+            },
+
+            _ => return Err(CommandError::UnknownVerb(verb))
+        };
+
+        args.finish()?;
+
+        return Ok(command)
+    }
+}
+
+// `x y [z]` then an elbow and a profile in either order, what the goto keys read from the buffer
+pub fn parse_position(text: &str) -> Result<Position, CommandError> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut args = Args::new("a position", &words);
+
+    let position = args.position(true)?;
+    args.finish()?;
+
+    return Ok(position)
+}
+
+// `<joint> <amount>`, what the motor key reads from the buffer
+pub fn parse_motor(text: &str) -> Result<(Joint, JointAmount), CommandError> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut args = Args::new("motor", &words);
+
+    let motor = (args.joint()?, args.joint_amount()?);
+    args.finish()?;
+
+    return Ok(motor)
+}
+
+// what the word before the cursor could be finished as, and the byte offset where that word starts. which list
+// it comes from depends on the words before it
+pub fn get_completions(before: &str, waypoints: &[String]) -> (usize, Vec<String>) {
+    let start = before.char_indices().rev().find(|(_, c)| c.is_whitespace()).map(|(i, c)| i + c.len_utf8()).unwrap_or(0);
+    let word = before[start..].to_lowercase();
+    let previous: Vec<String> = before[..start].split_whitespace().map(|x| x.to_lowercase()).collect();
+    let previous: Vec<&str> = previous.iter().map(|x| x.as_str()).collect();
+
+    let elbows = ["up", "down", "near", "safe"];
+    let profiles = ["poly", "trap", "scurve"];
+
+    let candidates: Vec<&str> = match previous.as_slice() {
+        [] => VERBS.iter().copied().chain(waypoints.iter().map(|x| x.as_str())).collect(),
+        ["set"] => SETTINGS.to_vec(),
+        ["set", "elbow"] => elbows.to_vec(),
+        ["set", "profile"] => profiles.to_vec(),
+        ["set", "coordinated"] => vec!["on", "off"],
+        ["motor"] => vec!["column", "beam", "base"],
+        ["goto", _, _] => elbows.to_vec(),
+        ["smooth", _, _, ..] | ["plan", _, _, ..] => elbows.iter().chain(profiles.iter()).copied().collect(),
+        ["set", ..] | ["motor", ..] | ["goto3d", ..] | ["save", ..] | ["load", ..] | ["run", ..] | ["home", ..] => vec![],
+        _ => waypoints.iter().map(|x| x.as_str()).collect()
+    };
+
+    let mut matches: Vec<String> = Vec::new();
+
+    for candidate in candidates {
+        if candidate.to_lowercase().starts_with(&word) && !matches.iter().any(|x| x == candidate) {
+            matches.push(candidate.to_string());
+        }
+    }
+
+    return (start, matches)
+}
+
+// the longest start every match shares
+pub fn get_common_prefix(matches: &[String]) -> String {
+    let first = match matches.first() {
+        Some(x) => x,
+        None => return String::new()
+    };
+
+    let mut prefix = first.clone();
+
+    for x in matches {
+        while !x.starts_with(&prefix) {
+            prefix.pop();
+        }
+    }
+
+    return prefix
+}
+
+// the words after the verb, taken one at a time
+struct Args<'a> {
+    verb: String,
+    words: &'a [&'a str],
+    next: usize
+}
+
+impl<'a> Args<'a> {
+    fn new(verb: &str, words: &'a [&'a str]) -> Args<'a> {
+        return Args { verb: verb.to_string(), words, next: 0 }
+    }
+
+    fn word(&mut self, what: &str) -> Result<&'a str, CommandError> {
+        match self.words.get(self.next) {
+            Some(x) => {
+                self.next += 1;
+                Ok(x)
+            },
+
+            None => Err(CommandError::Missing(self.verb.clone(), what.to_string()))
+        }
+    }
+
+    fn quantity(&mut self, what: &str, units: &[(&str, f32)]) -> Result<f32, CommandError> {
+        let word = self.word(what)?;
+
+        return parse_quantity(word, units)
+    }
+
+    fn length(&mut self, what: &str) -> Result<f32, CommandError> {
+        return self.quantity(what, &LENGTH_UNITS)
+    }
+
+    // a word that may or may not be there, left alone when it isn't one
+    fn optional<T>(&mut self, parse: impl Fn(&str) -> Option<T>, what: &str) -> Result<Option<T>, CommandError> {
+        let word = match self.words.get(self.next) {
+            Some(x) => *x,
+            None => return Ok(None)
+        };
+
+        match parse(word) {
+            Some(x) => {
+                self.next += 1;
+                Ok(Some(x))
+            },
+
+            None => Err(CommandError::Invalid(word.to_string(), what.to_string()))
+        }
+    }
+
+    // a number next, rather than a word
+    fn optional_length(&mut self) -> Result<Option<f32>, CommandError> {
+        match self.words.get(self.next) {
+            Some(x) if !split_unit(x).0.is_empty() => Ok(Some(self.length("a number")?)),
+            _ => Ok(None)
+        }
+    }
+
+    fn position(&mut self, with_z: bool) -> Result<Position, CommandError> {
+        let (x, y) = (self.length("x y")?, self.length("x y")?);
+        let z = if with_z { self.optional_length()? } else { None };
+        let mut policy = None;
+        let mut profile = None;
+
+        while let Some(word) = self.words.get(self.next) {
+            if let Some(x) = ElbowPolicy::parse(word) {
+                policy = Some(x);
+            } else if let Some(x) = ProfileKind::parse(word) {
+                profile = Some(x);
+            } else {
+                return Err(CommandError::Invalid(word.to_string(), String::from("an elbow (up, down, near, safe) or a profile (poly, trap, scurve)")))
+            }
+
+            self.next += 1;
+        }
+
+        return Ok(Position { x, y, z, policy, profile })
+    }
+
+    fn joint(&mut self) -> Result<Joint, CommandError> {
+        let word = self.word("column, beam or base")?;
+
+        return Joint::parse(word).ok_or(CommandError::Invalid(word.to_string(), String::from("column, beam or base")))
+    }
+
+    // plain or `st` for steps, `deg` or `rad` for an angle
+    fn joint_amount(&mut self) -> Result<JointAmount, CommandError> {
+        let word = self.word("steps or an angle")?;
+        let (_, unit) = split_unit(word);
+
+        if unit.is_empty() || unit == "st" {
+            return match word.trim_end_matches("st").parse::<i32>() {
+                Ok(x) => Ok(JointAmount::Steps(x)),
+                Err(_) => Err(CommandError::Invalid(word.to_string(), String::from("a whole number of steps")))
+            }
+        }
+
+        match parse_quantity(word, &ANGLE_UNITS) {
+            Ok(x) => Ok(JointAmount::Degrees(x)),
+            Err(CommandError::UnknownUnit(word, _)) => Err(CommandError::UnknownUnit(word, String::from("st, deg or rad"))),
+            Err(e) => Err(e)
+        }
+    }
+
+    fn setting(&mut self) -> Result<Setting, CommandError> {
+        let name = self.word("a setting and a value")?.to_lowercase();
+        self.verb = format!("set {}", name);
+
+        let positive = |x: f32, word: &str| -> Result<f32, CommandError> {
+            if x > 0.0 { return Ok(x) }
+            Err(CommandError::Invalid(word.to_string(), String::from("above zero")))
+        };
+
+        let setting = match name.as_str() {
+            "delay" => Setting::Delay(self.quantity("a delay", &DELAY_UNITS)?.round() as i64),
+            "min-delay" => Setting::MinDelay(self.quantity("a delay", &DELAY_UNITS)?.round() as i64),
+            "max-delay" => Setting::MaxDelay(self.quantity("a delay", &DELAY_UNITS)?.round() as i64),
+
+            "movement" => {
+                let x = self.length("a distance")?;
+                Setting::Movement(positive(x, self.words[self.next - 1])?)
+            },
+
+            "step" => {
+                let word = self.word("a number of steps")?;
+
+                match word.parse::<i32>() {
+                    Ok(x) if x > 0 => Setting::StepAmount(x),
+                    _ => return Err(CommandError::Invalid(word.to_string(), String::from("a whole number of steps above zero")))
+                }
+            },
+
+            "elbow" => {
+                let word = self.word("an elbow")?;
+                Setting::Elbow(ElbowPolicy::parse(word).ok_or(CommandError::Invalid(word.to_string(), String::from("up, down, near or safe")))?)
+            },
+
+            "profile" => {
+                let word = self.word("a profile")?;
+                Setting::Profile(ProfileKind::parse(word).ok_or(CommandError::Invalid(word.to_string(), String::from("poly, trap or scurve")))?)
+            },
+
+            "velocity" => {
+                let x = self.quantity("a velocity", &VELOCITY_UNITS)?;
+                Setting::Velocity(positive(x, self.words[self.next - 1])?)
+            },
+
+            "acceleration" => {
+                let x = self.quantity("an acceleration", &ACCELERATION_UNITS)?;
+                Setting::Acceleration(positive(x, self.words[self.next - 1])?)
+            },
+
+            "tool-speed" => {
+                let x = self.quantity("a speed", &SPEED_UNITS)?;
+                Setting::ToolSpeed(positive(x, self.words[self.next - 1])?)
+            },
+
+            "coordinated" => match self.word("on or off")? {
+                "on" | "true" | "yes" => Setting::Coordinated(true),
+                "off" | "false" | "no" => Setting::Coordinated(false),
+                x => return Err(CommandError::Invalid(x.to_string(), String::from("on or off")))
+            },
+
+            _ => return Err(CommandError::Invalid(name, format!("a setting, use {}", SETTINGS.join(", "))))
+        };
+
+        return Ok(setting)
+    }
+
+    // everything left as one string, for paths with spaces in them
+    fn rest(&mut self) -> Option<String> {
+        if self.next >= self.words.len() { return None }
+
+        let rest = self.words[self.next..].join(" ");
+        self.next = self.words.len();
+
+        return Some(rest)
+    }
+
+    fn finish(&self) -> Result<(), CommandError> {
+        match self.words.get(self.next) {
+            Some(x) => Err(CommandError::Unexpected(x.to_string())),
+            None => Ok(())
+        }
+    }
+}
+
+// the number at the front and whatever follows it
+fn split_unit(word: &str) -> (&str, &str) {
+    let end = word.char_indices()
+        .find(|(i, c)| !(c.is_ascii_digit() || *c == '.' || ((*c == '-' || *c == '+') && *i == 0)))
+        .map(|(i, _)| i)
+        .unwrap_or(word.len());
+
+    return word.split_at(end)
+}
+
+fn parse_quantity(word: &str, units: &[(&str, f32)]) -> Result<f32, CommandError> {
+    let (number, unit) = split_unit(word);
+
+    let value = match number.parse::<f32>() {
+        Ok(x) if x.is_finite() => x,
+        _ => return Err(CommandError::NotANumber(word.to_string()))
+    };
+
+    if unit.is_empty() { return Ok(value) }
+
+    match units.iter().find(|(name, _)| *name == unit.to_lowercase()) {
+        Some((_, scale)) => Ok(value * scale),
+        None => Err(CommandError::UnknownUnit(word.to_string(), units.iter().map(|(x, _)| *x).collect::<Vec<_>>().join(", ")))
+    }
+}
+
+impl App {
+    // enter in buffer mode. a buffer that isn't a command is kept for the keys that read it
+    pub fn run_buffer(&mut self) {
+        if let Err(e) = self.history.push(&self.buffer) {
+            self.command_output.insert(format!("unable to save history: {}", e));
+        }
+
+        if !BufferCommand::is_command(&self.buffer) {
+            self.current_mode = Mode::Normal;
+            return
+        }
+
+        match BufferCommand::parse(&self.buffer) {
+            Ok(x) => {
+                self.execute_command(x);
+                self.clear_buffer();
+                self.current_mode = Mode::Normal;
+            },
+
+            Err(e) => self.command_output.insert(e.get_message())
+        }
+    }
+
+    // why the buffer won't run, for highlighting it while it's typed. None for anything that isn't a command
+    pub fn get_buffer_error(&self) -> Option<String> {
+        if !BufferCommand::is_command(&self.buffer) { return None }
+
+        match BufferCommand::parse(&self.buffer) {
+            Ok(BufferCommand::GotoWaypoint(name)) if !self.program.waypoints.iter().any(|x| x.name == name) => {
+                Some(format!("no waypoint named {}", name))
+            },
+
+            Ok(_) => None,
+            Err(e) => Some(e.get_message())
+        }
+    }
+
+    pub fn execute_command(&mut self, command: BufferCommand) {
+        match command {
+            BufferCommand::Goto(x, y, policy) => {
+                self.add_current_position();
+
+                self.submit(move |driver| {
+                    let policy = policy.unwrap_or(driver.elbow_policy);
+
+                    driver.goto_point_with(x, y, policy).map(|_| format!("successfully went to point {} {}", x, y))
+                });
+            },
+
+            BufferCommand::GotoWaypoint(name) => {
+                let waypoint = match self.program.waypoints.iter().find(|x| x.name == name) {
+                    Some(x) => x.clone(),
+                    None => {
+                        self.command_output.insert(format!("no waypoint named {}", name));
+                        return
+                    }
+                };
+
+                self.add_current_position();
+
+                self.executor.submit(Box::new(move |driver| {
+                    match waypoint.execute(driver) {
+                        Ok(()) => Ok(format!("moved to {}", waypoint.name)),
+                        Err(e) => Err(ProgramError::Driver(waypoint.name.clone(), e).get_message())
+                    }
+                }));
+            },
+
+            BufferCommand::Goto3d(x, y, z, policy) => {
+                self.add_current_position();
+
+                self.submit(move |driver| {
+                    let policy = policy.unwrap_or(driver.elbow_policy);
+
+                    driver.goto_point_3d_with(x, y, z, policy).map(|_| format!("moved to {} {} {}", x, y, z))
+                });
+            },
+
+            BufferCommand::Smooth(x, y, policy, profile) => {
+                self.add_current_position();
+
+                self.submit(move |driver| {
+                    let policy = policy.unwrap_or(driver.elbow_policy);
+                    let profile = profile.unwrap_or(driver.profile);
+
+                    driver.goto_point_smooth_with(x, y, policy, profile).map(|_| format!("moved smoothly to {} {}", x, y))
+                });
+            },
+
+            BufferCommand::Plan(position) => {
+                let plan = self.with_driver(|driver| {
+                    let policy = position.policy.unwrap_or(driver.elbow_policy);
+
+                    match position.z {
+                        Some(z) => driver.plan_point_3d(position.x, position.y, z, policy, position.profile),
+                        None => driver.plan_point(position.x, position.y, policy, position.profile)
+                    }
+                });
+
+                match plan {
+                    Some(Ok(x)) => self.command_output.insert(x.describe()),
+                    Some(Err(e)) => self.command_output.insert(App::get_error_message(&e)),
+                    None => {}
+                }
+            },
+
+            BufferCommand::Motor(joint, amount) => {
+                self.submit(move |driver| {
+                    let steps = amount.get_steps(driver, joint);
+
+                    driver.move_joint(joint, steps).map(|_| format!("moved {} {} steps", joint.get_name(), steps))
+                });
+            },
+
+            BufferCommand::Set(setting) => {
+                if let Some(result) = self.with_driver(|driver| setting.apply(driver)) {
+                    match result {
+                        Ok(x) => self.command_output.insert(x),
+                        Err(e) => self.command_output.insert(e)
+                    }
+                }
+            },
+
+            BufferCommand::Save(path) => self.save_program(path.unwrap_or(String::from(DEFAULT_PROGRAM_PATH))),
+
+            BufferCommand::Load(path) => self.load_program(path.unwrap_or(String::from(DEFAULT_PROGRAM_PATH))),
+
+            BufferCommand::Home => self.home(),
+
+            BufferCommand::Run(path) => self.run_gcode(path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_take_typed_arguments() {
+        assert_eq!(BufferCommand::parse("goto 1.5 0.5"), Ok(BufferCommand::Goto(1.5, 0.5, None)));
+        assert_eq!(BufferCommand::parse("GOTO 1 1 down"), Ok(BufferCommand::Goto(1.0, 1.0, Some(ElbowPolicy::Down))));
+        assert_eq!(BufferCommand::parse("goto3d 1 0.5 -0.2"), Ok(BufferCommand::Goto3d(1.0, 0.5, -0.2, None)));
+        assert_eq!(BufferCommand::parse("goto 1 0.5 0.3 down"), Ok(BufferCommand::Goto3d(1.0, 0.5, 0.3, Some(ElbowPolicy::Down))));
+        assert_eq!(BufferCommand::parse("smooth 1 1 trap near"),
+                   Ok(BufferCommand::Smooth(1.0, 1.0, Some(ElbowPolicy::Nearest), Some(ProfileKind::Trapezoidal))));
+        assert_eq!(BufferCommand::parse("motor base -200"), Ok(BufferCommand::Motor(Joint::Base, JointAmount::Steps(-200))));
+        assert_eq!(BufferCommand::parse("save my program.txt"), Ok(BufferCommand::Save(Some(String::from("my program.txt")))));
+        assert_eq!(BufferCommand::parse("load"), Ok(BufferCommand::Load(None)));
+        assert_eq!(BufferCommand::parse("run part.gcode"), Ok(BufferCommand::Run(String::from("part.gcode"))));
+        assert_eq!(BufferCommand::parse("set coordinated off"), Ok(BufferCommand::Set(Setting::Coordinated(false))));
+        assert_eq!(BufferCommand::parse("  home  "), Ok(BufferCommand::Home));
+        assert_eq!(BufferCommand::parse("goto pick"), Ok(BufferCommand::GotoWaypoint(String::from("pick"))));
+        assert_eq!(BufferCommand::parse("plan 1 0.5 0.2 scurve"),
+                   Ok(BufferCommand::Plan(Position { x: 1.0, y: 0.5, z: Some(0.2), policy: None, profile: Some(ProfileKind::SCurve) })));
+    }
+
+    #[test]
+    fn units_convert_to_what_the_driver_uses() {
+        assert_eq!(BufferCommand::parse("set delay 2.5ms"), Ok(BufferCommand::Set(Setting::Delay(2500))));
+        assert_eq!(BufferCommand::parse("set max-delay 4000us"), Ok(BufferCommand::Set(Setting::MaxDelay(4000))));
+        assert_eq!(BufferCommand::parse("set tool-speed 15/min"), Ok(BufferCommand::Set(Setting::ToolSpeed(0.25))));
+        assert_eq!(BufferCommand::parse("motor beam 20st"), Ok(BufferCommand::Motor(Joint::Beam, JointAmount::Steps(20))));
+        assert_eq!(BufferCommand::parse("motor beam -15deg"), Ok(BufferCommand::Motor(Joint::Beam, JointAmount::Degrees(-15.0))));
+
+        match BufferCommand::parse("set velocity 1rad/s") {
+            Ok(BufferCommand::Set(Setting::Velocity(x))) => assert!((x - 57.29578).abs() < 1e-3),
+            x => panic!("{:?}", x)
+        }
+    }
+
+    #[test]
+    fn malformed_input_is_an_error_not_a_panic() {
+        let bad = ["", "   ", "goto", "goto 1", "goto x 1", "goto 1 1 sideways", "goto 1 1 up 2", "goto3d 1 1", "smooth 1",
+                   "motor", "motor elbow 20", "motor beam", "motor beam 2.5", "motor beam 20km", "set", "set delay",
+                   "set delay fast", "set delay 5km", "set speed 3", "set step -5", "set velocity -1", "set coordinated maybe",
+                   "run", "home now", "jump 1 1", "goto ééé 1", "goto 1e 1", "goto - -", "goto 1 nan", "set delay inf"];
+
+        for text in bad {
+            assert!(BufferCommand::parse(text).is_err(), "{} parsed", text);
+        }
+
+        assert_eq!(BufferCommand::parse("goto 1"), Err(CommandError::Missing(String::from("goto"), String::from("x y"))));
+        assert!(parse_position("1").is_err());
+        assert!(BufferCommand::parse("smooth 1 1 0.5").is_err());
+        assert_eq!(parse_position("1 0.5 -0.2 trap").map(|x| (x.z, x.profile)), Ok((Some(-0.2), Some(ProfileKind::Trapezoidal))));
+        assert!(parse_motor("column").is_err());
+        assert_eq!(parse_motor("column 10"), Ok((Joint::Column, JointAmount::Steps(10))));
+    }
+
+    #[test]
+    fn completions_depend_on_the_words_before() {
+        let waypoints = vec![String::from("pick"), String::from("place"), String::from("home2")];
+
+        assert_eq!(get_completions("go", &waypoints), (0, vec![String::from("goto"), String::from("goto3d")]));
+        assert_eq!(get_completions("ho", &waypoints), (0, vec![String::from("home"), String::from("home2")]));
+        assert_eq!(get_completions("goto p", &waypoints), (5, vec![String::from("pick"), String::from("place")]));
+        assert_eq!(get_completions("set  max", &waypoints), (5, vec![String::from("max-delay")]));
+        assert_eq!(get_completions("set elbow d", &waypoints).1, vec![String::from("down")]);
+        assert_eq!(get_completions("motor b", &waypoints).1, vec![String::from("beam"), String::from("base")]);
+        assert!(get_completions("run p", &waypoints).1.is_empty());
+        // wider spaces typed from an input method are still only spaces
+        assert_eq!(get_completions("goto\u{3000}", &[]), (7, vec![]));
+        assert_eq!(get_completions("set\u{3000}coor", &[]), (6, vec![String::from("coordinated")]));
+
+        assert_eq!(get_common_prefix(&[String::from("goto"), String::from("goto3d")]), "goto");
+        assert_eq!(get_common_prefix(&[String::from("pick"), String::from("place")]), "p");
+    }
+}
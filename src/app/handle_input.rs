@@ -1,13 +1,11 @@
 use crate::app::{App, Mode, Event};
 use crate::driver;
 
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use crossterm::terminal::disable_raw_mode;
 use std::sync::mpsc::Receiver;
 
 use std::io::Stdout;
-use std::thread;
-use std::time::Duration;
 
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
@@ -15,8 +13,17 @@ use tui::Terminal;
 impl App {
     pub fn handle_input(&mut self, rx: &Receiver<Event<KeyEvent>>, terminal: &mut Terminal<CrosstermBackend<Stdout>>) {
         match rx.recv().unwrap() {
-            Event::Input(event) => match event.code {
-                event => match self.current_mode {
+            // ctrl-c stops the arm from any mode, even while typing in the buffer
+            Event::Input(event) if event.code == KeyCode::Char('c') && event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.emergency_stop()
+            },
+
+            Event::Input(event) if matches!(self.current_mode, Mode::Buffer) => { self.handle_buffer_key(event) },
+
+            Event::Input(event) => {
+                let event = event.code;
+
+                match self.current_mode {
                     Mode::Normal => match event {
                         KeyCode::Esc => {
                             self.current_mode = Mode::Normal 
@@ -24,7 +31,16 @@ impl App {
 
                         KeyCode::Char('c') => { self.current_mode = Mode::Control }
 
-                        KeyCode::Char(':') => { self.current_mode = Mode::Buffer }
+                        KeyCode::Char(' ') => { self.cancel_motion() },
+
+                        KeyCode::Char('!') => { self.emergency_stop() },
+
+                        KeyCode::Char('t') => { self.current_mode = Mode::Teach }
+
+                        KeyCode::Char(':') => {
+                            self.current_mode = Mode::Buffer;
+                            self.buffer_cursor = self.buffer.chars().count();
+                        },
 
                         KeyCode::Char('q') => {
                             disable_raw_mode().unwrap();
@@ -39,6 +55,10 @@ impl App {
 
                         KeyCode::Char('s') => { self.save_current_angles(); },
 
+                        KeyCode::Char('x') => { self.save_step_traces(); },
+
+                        KeyCode::Char('h') => { self.home(); },
+
                         KeyCode::Char('p') => { self.flush_prev_positions(); },
 
                         KeyCode::Char('f') => { self.flush_command_output(); },
@@ -57,6 +77,10 @@ impl App {
                     Mode::Control => match event {
                         KeyCode::Esc => { self.current_mode = Mode::Normal},
 
+                        KeyCode::Char(' ') => { self.cancel_motion() },
+
+                        KeyCode::Char('!') => { self.emergency_stop() },
+
                         KeyCode::Left => { self.move_direction(driver::Direction::Left); },
 
                         KeyCode::Right => { self.move_direction(driver::Direction::Right); },
@@ -69,6 +93,12 @@ impl App {
 
                         KeyCode::Char('\\') => { self.goto_smooth(); },
 
+                        KeyCode::Char('l') => { self.goto_line(); },
+
+                        KeyCode::Char('o') => { self.goto_arc(); },
+
+                        KeyCode::Char('g') => { self.run_gcode(self.buffer.trim().to_string()); },
+
                         KeyCode::Char('=') => { self.increase_movement_amount(); },
 
                         KeyCode::Char('-') => { self.decrease_movement_amount(); },
@@ -85,6 +115,8 @@ impl App {
 
                         KeyCode::Char('.') => { self.increase_delay(); },
 
+                        KeyCode::Char('s') => { self.toggle_coordinated() },
+
                         KeyCode::Char('r') => { self.move_motor() },
 
                         KeyCode::Char('m') => { self.increase_generic_step_amount() },
@@ -99,26 +131,65 @@ impl App {
 
                         KeyCode::Char('d') => { self.move_column_pos() },
 
+                        KeyCode::Char('z') => { self.move_base_neg() },
+
+                        KeyCode::Char('c') => { self.move_base_pos() },
+
                         _ => {}
                     },
 
-                    Mode::Buffer => match event {
-                        KeyCode::Esc => { self.current_mode = Mode::Normal; },
+                    Mode::Teach => match event {
+                        KeyCode::Esc => { self.current_mode = Mode::Normal },
+
+                        KeyCode::Char(' ') => { self.cancel_motion() },
+
+                        KeyCode::Char('!') => { self.emergency_stop() },
+
+                        KeyCode::Left => { self.move_direction(driver::Direction::Left); },
+
+                        KeyCode::Right => { self.move_direction(driver::Direction::Right); },
+
+                        KeyCode::Up => { self.move_direction(driver::Direction::Up); },
+
+                        KeyCode::Down => { self.move_direction(driver::Direction::Down); },
+
+                        KeyCode::Char('q') => { self.move_beam_neg() },
+
+                        KeyCode::Char('e') => { self.move_beam_pos() },
+
+                        KeyCode::Char('a') => { self.move_column_neg() },
+
+                        KeyCode::Char('d') => { self.move_column_pos() },
+
+                        KeyCode::Char('z') => { self.move_base_neg() },
+
+                        KeyCode::Char('c') => { self.move_base_pos() },
+
+                        KeyCode::Char('w') => { self.record_waypoint() },
 
-                        KeyCode::Enter => { self.current_mode = Mode::Normal; }
+                        KeyCode::Char('u') => { self.remove_last_waypoint() },
 
-                        KeyCode::Char(c) => { self.buffer.push(c); },
+                        KeyCode::Char('s') => { self.save_program(self.get_program_path()) },
 
-                        KeyCode::Backspace => { self.buffer.pop(); },
+                        KeyCode::Char('l') => { self.load_program(self.get_program_path()) },
 
-                        KeyCode::Delete => { self.buffer.clear(); }
+                        KeyCode::Char('r') => { self.start_replay() },
+
+                        KeyCode::Char('n') => { self.step_replay() },
+
+                        KeyCode::Char('p') => { self.toggle_replay_pause() },
+
+                        KeyCode::Char('x') => { self.stop_replay() },
 
                         _ => {}
-                    }
+                    },
+
+                    // keys go to handle_buffer_key while typing
+                    Mode::Buffer => {}
                 }
             },
 
-            Event::Tick => {}
+            Event::Tick => { self.tick_replay() }
         }
     }
 }
@@ -0,0 +1,124 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+// lines entered in buffer mode, oldest first. every new line is appended to the file so a crash doesn't lose
+// them, and the file is cut back to the newest `size` when it's loaded
+pub struct History {
+    entries: Vec<String>,
+    position: Option<usize>, // which entry up and down are on, None while editing a new line
+    draft: String, // what was being typed before going up
+    path: String,
+    size: usize
+}
+
+impl History {
+    pub fn load(path: &str, size: usize) -> History {
+        let mut entries: Vec<String> = match std::fs::read_to_string(path) {
+            Ok(x) => x.lines().filter(|x| !x.trim().is_empty()).map(String::from).collect(),
+            Err(_) => Vec::new()
+        };
+
+        if entries.len() > size {
+            entries.drain(..entries.len() - size);
+            let _ = std::fs::write(path, entries.iter().map(|x| format!("{}\n", x)).collect::<String>());
+        }
+
+        return History { entries, position: None, draft: String::new(), path: path.to_string(), size }
+    }
+
+    // repeats of the last line aren't kept twice
+    pub fn push(&mut self, line: &str) -> Result<(), std::io::Error> {
+        self.position = None;
+
+        let line = line.trim();
+
+        if line.is_empty() || self.entries.last().is_some_and(|x| x == line) { return Ok(()) }
+
+        self.entries.push(line.to_string());
+
+        if self.entries.len() > self.size { self.entries.remove(0); }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+
+        Ok(())
+    }
+
+    // the entry before the one showing, `current` is kept to come back to
+    pub fn previous(&mut self, current: &str) -> Option<String> {
+        let position = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            },
+            Some(0) => return None,
+            Some(x) => x - 1
+        };
+
+        self.position = Some(position);
+
+        return Some(self.entries[position].clone())
+    }
+
+    // the entry after the one showing, past the newest is whatever was being typed
+    pub fn next(&mut self) -> Option<String> {
+        let position = self.position?;
+
+        if position + 1 >= self.entries.len() {
+            self.position = None;
+            return Some(self.draft.clone())
+        }
+
+        self.position = Some(position + 1);
+
+        return Some(self.entries[position + 1].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("arm_history_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        return path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn up_and_down_walk_the_history_and_come_back() {
+        let path = test_path("walk");
+        let mut history = History::load(&path, 10);
+
+        history.push("goto 1 1").unwrap();
+        history.push("home").unwrap();
+        history.push("home").unwrap();
+
+        assert_eq!(history.previous("goto 1."), Some(String::from("home")));
+        assert_eq!(history.previous("home"), Some(String::from("goto 1 1")));
+        assert_eq!(history.previous("goto 1 1"), None);
+        assert_eq!(history.next(), Some(String::from("home")));
+        assert_eq!(history.next(), Some(String::from("goto 1.")));
+        assert_eq!(history.next(), None);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn history_is_kept_between_runs_up_to_its_size() {
+        let path = test_path("persist");
+        let mut history = History::load(&path, 2);
+
+        for line in ["goto 1 1", "smooth 1 0.5", "run a.gcode"] {
+            history.push(line).unwrap();
+        }
+
+        let history = History::load(&path, 2);
+        assert_eq!(history.entries, vec![String::from("smooth 1 0.5"), String::from("run a.gcode")]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
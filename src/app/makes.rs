@@ -1,13 +1,13 @@
-use crate::app::App;
+use crate::app::{App, Mode};
 
 use tui::layout::{Direction, Constraint, Layout};
 use tui::symbols;
-use tui::style::Style;
+use tui::style::{Color, Modifier, Style};
 use tui::text::{Span, Spans};
 use tui::widgets::{Paragraph, Block, Borders, ListItem, List, Dataset, Chart, Axis, GraphType, ListState};
 
 impl App {
-    pub fn make_plain_paragraph(&self, content: String) -> Paragraph {
+    pub fn make_plain_paragraph(&self, content: String) -> Paragraph<'_> {
         let paragraph = Paragraph::new(content)
             .style(Style::default())
             .block(
@@ -19,7 +19,7 @@ impl App {
         return paragraph
     }
 
-    pub fn make_map<'a>(&'a mut self, data: &'a Vec<(f64, f64)>, title: String, x_bounds: [f64; 2], y_bounds: [f64; 2]) -> Chart {
+    pub fn make_map<'a>(&'a mut self, data: &'a [(f64, f64)], title: String, x_bounds: [f64; 2], y_bounds: [f64; 2]) -> Chart<'a> {
         let dataset = vec![
             Dataset::default()
                 .marker(symbols::Marker::Braille)
@@ -45,21 +45,43 @@ impl App {
         return map;
     }
 
-    pub fn make_buffer(&mut self) -> Paragraph {
-        let buffer = Paragraph::new(self.buffer.clone())
+    // red with the reason in the title while the command in it won't run, and the cursor shown while typing
+    pub fn make_buffer(&mut self) -> Paragraph<'_> {
+        let error = self.get_buffer_error();
+        let style = if error.is_some() { Style::default().fg(Color::Red) } else { Style::default() };
+
+        let text = match self.current_mode {
+            Mode::Buffer => {
+                let cursor = self.get_buffer_cursor();
+                let before: String = self.buffer.chars().take(cursor).collect();
+                let under: String = self.buffer.chars().nth(cursor).map(String::from).unwrap_or(String::from(" "));
+                let after: String = self.buffer.chars().skip(cursor + 1).collect();
+
+                Spans::from(vec![
+                    Span::styled(before, style),
+                    Span::styled(under, style.add_modifier(Modifier::REVERSED)),
+                    Span::styled(after, style)
+                ])
+            },
+
+            _ => Spans::from(Span::styled(self.buffer.clone(), style))
+        };
+
+        let buffer = Paragraph::new(text)
             .style(Style::default())
             .alignment(tui::layout::Alignment::Center)
             .block(
                 Block::default()
+                    .title(error.unwrap_or_default())
                     .borders(Borders::ALL)
-                    .style(Style::default())
+                    .style(style)
                     .border_type(tui::widgets::BorderType::Plain)
             );
 
         return buffer
     }
 
-    pub fn make_config_window(&mut self) -> Paragraph {
+    pub fn make_config_window(&mut self) -> Paragraph<'_> {
         let config_data = self.make_config_text();
         let config = Paragraph::new(config_data)
             .block(
@@ -71,12 +93,12 @@ impl App {
         return config
     }
 
-    pub fn make_previous_points(&mut self) -> (List, &mut ListState) {
+    pub fn make_previous_points(&mut self) -> (List<'_>, &mut ListState) {
        let items: Vec<ListItem> = self.prev_positions.get_items()
             .iter()
             .map(|i| {
                 let content = Spans::from(Span::styled(
-                    format!("{} {}", i.column_angle, i.beam_angle),
+                    format!("{} {} {}", i.column_angle, i.beam_angle, i.rotation_angle),
                     Style::default() 
                 ));
 
@@ -94,12 +116,12 @@ impl App {
         return (prev_items, self.prev_positions.get_state())
     }
 
-    pub fn make_command_output(&mut self) -> (List, &mut ListState) {
+    pub fn make_command_output(&mut self) -> (List<'_>, &mut ListState) {
         let command_items: Vec<ListItem> = self.command_output.get_items()
             .iter()
             .map(|i| {
                 let content = Spans::from(Span::styled(
-                    format!("{}", i),
+                    i.clone(),
                     Style::default() 
                 ));
 
@@ -118,7 +140,7 @@ impl App {
         return (command_items, self.prev_positions.get_state())
     }
 
-    pub fn make_current_mode_box(&mut self) -> Paragraph {
+    pub fn make_current_mode_box(&mut self) -> Paragraph<'_> {
         let current_mode_box = Paragraph::new(self.get_current_mode_string())
             .style(Style::default())
             .alignment(tui::layout::Alignment::Center)
@@ -141,21 +163,12 @@ impl App {
         return text
     }
 
-    pub fn make_config_text(&self) -> Vec<Spans>{
-        let beam = self.driver.get_beam_position();
-        let column = self.driver.get_column_position();
-
-        let text = vec![
-            App::make_info_span(String::from("DELAY: "), format!("{}", self.driver.micro_delay_default)),
-            App::make_info_span(String::from("MAX DELAY: "), format!("{}", self.driver.micro_delay_max)),
-            App::make_info_span(String::from("MIN DELAY: "), format!("{}", self.driver.micro_delay_min)),
-            App::make_info_span(String::from("MVNT AMT: "), format!("{}", self.driver.movement_amount)),
-            App::make_info_span(String::from("BEAM X: "), format!("{}", beam.x)),
-            App::make_info_span(String::from("BEAM Y: "), format!("{}", beam.y)),
-            App::make_info_span(String::from("COLUMN X: "), format!("{}", column.x)),
-            App::make_info_span(String::from("COLUMN Y: "), format!("{}", column.y)),
-            App::make_info_span(String::from("STEP AMT: "), format!("{}", self.driver.generic_step_amount)),
-        ];
+    pub fn make_config_text(&self) -> Vec<Spans<'_>>{
+        let mut text: Vec<Spans> = self.view.info.iter().map(|(name, value)| App::make_info_span(name.clone(), value.clone())).collect();
+
+        text.push(App::make_info_span(String::from("MOTION: "), self.get_motion_status()));
+        text.push(App::make_info_span(String::from("PROGRAM: "), format!("{} waypoints", self.program.waypoints.len())));
+        text.push(App::make_info_span(String::from("REPLAY: "), self.get_replay_status()));
 
         return text
     }
@@ -164,7 +177,7 @@ impl App {
         let chunk = Layout::default()
             .direction(direction)
             .margin(0)
-            .constraints(constraints.as_ref());
+            .constraints(constraints);
 
         return chunk
     }
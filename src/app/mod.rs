@@ -1,9 +1,20 @@
 mod handle_input;
 mod makes;
 mod backend;
+mod teach;
+mod view;
+mod command;
+mod buffer;
+mod history;
 
-use crate::driver::Driver;
+pub use view::DriverView;
+
+use crate::driver::{Driver, Backend};
+use crate::executor::Executor;
 use crate::utils::ShiftingVec;
+use crate::config::Config;
+use crate::program::{Program, Replay};
+use history::History;
 
 use crossterm::event;
 use crossterm::terminal::enable_raw_mode;
@@ -11,12 +22,11 @@ use crossterm::terminal::enable_raw_mode;
 use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant};
-use std::sync::Arc;
 
 
 use tui::Terminal;
 use tui::backend::CrosstermBackend;
-use tui::layout::{Layout, Direction, Constraint};
+use tui::layout::{Direction, Constraint};
 
 pub enum Event<I> {
     Input(I),
@@ -26,6 +36,7 @@ pub enum Event<I> {
 pub enum Mode {
     Normal,
     Control,
+    Teach,
     Buffer
 }
 
@@ -43,27 +54,46 @@ pub struct App {
     command_output_size: usize,
     current_mode: Mode,
     buffer: String,
-    driver: Driver,
+    buffer_cursor: usize,
+    history: History,
+    executor: Executor,
+    view: DriverView,
+    program: Program,
+    replay: Option<Replay>,
+    replay_job: Option<u64>, // the waypoint the executor is running for the replay
 }
 
 impl App {
-    pub fn new() -> App {
-        let shifting_vec_size = 10;
+    // the lines to print when it can't start
+    pub fn new(backend: Backend, config_path: &str) -> Result<App, Vec<String>> {
+        let (config, config_messages) = match Config::load(config_path) {
+            Ok(x) => (x, vec![format!("loaded config from {}", config_path)]),
+            // the default pins could be wired to anything on another arm, so they're only used on simulated motors
+            Err(e) if backend == Backend::Hardware => return Err(e.get_messages()),
+            Err(e) => (Config::default(), [e.get_messages(), vec![String::from("using the default config")]].concat())
+        };
 
         let default_angleset = AngleSet { column_angle: 0.0, beam_angle: 0.0, rotation_angle: 0.0 };
-        let prev_positions = ShiftingVec::<AngleSet>::initalize(shifting_vec_size, default_angleset);
-        let prev_positions_size = 10;
+        let prev_positions_size = config.ui.prev_positions;
+        let prev_positions = ShiftingVec::<AngleSet>::initalize(prev_positions_size, default_angleset);
 
         let default_output = String::from("");
-        let command_output = ShiftingVec::<String>::initalize(shifting_vec_size, default_output);
-        let command_output_size = 10;
+        let command_output_size = config.ui.command_output;
+        let mut command_output = ShiftingVec::<String>::initalize(command_output_size, default_output);
+
+        for message in config_messages {
+            command_output.insert(message);
+        }
 
         let current_mode = Mode::Normal;
         let buffer = String::from("");
-        let driver = Driver::new();
+        let history = History::load(&config.ui.history, config.ui.history_size);
+        let driver = Driver::new(backend, &config).map_err(|e| vec![format!("unable to start driver: {}", e.get_message())])?;
+        let view = DriverView::new(&driver);
+        let executor = Executor::new(driver);
 
-        return App { prev_positions, command_output, current_mode, buffer, driver, prev_positions_size,
-            command_output_size }
+        Ok(App { prev_positions, command_output, current_mode, buffer, buffer_cursor: 0, history, executor, view, prev_positions_size,
+            command_output_size, program: Program::default(), replay: None, replay_job: None })
     }
 
     pub fn start(&mut self) {
@@ -87,10 +117,8 @@ impl App {
                     }
                 }
 
-                if last_tick.elapsed() >= tick_rate {
-                    if let Ok(_) = tx.send(Event::Tick) {
-                        last_tick = Instant::now();
-                    }
+                if last_tick.elapsed() >= tick_rate && tx.send(Event::Tick).is_ok() {
+                    last_tick = Instant::now();
                 }
             }
         });
@@ -101,6 +129,8 @@ impl App {
         terminal.clear().unwrap();
 
         loop {
+            self.poll_jobs();
+
             terminal.draw(|rect| {
                 let size = rect.size();
                 let chunks = App::make_chunk(
@@ -211,11 +241,11 @@ impl App {
                 let x_z_map = self.make_map(&x_z_data, String::from("X-Z"), [0.0, 2.0], [-2.0, 2.0]);
                 rect.render_widget(x_z_map, middle_right_top_chunks[0]);
 
-                let true_x_y_data = vec![(self.driver.current_position.x as f64, self.driver.current_position.y as f64)];
+                let true_x_y_data = vec![(self.view.position.x as f64, self.view.position.y as f64)];
                 let true_x_y_map = self.make_map(&true_x_y_data, String::from("True X-Y"), [0.0, 2.0], [0.0, 2.0]);
                 rect.render_widget(true_x_y_map, middle_right_top_chunks[1]);
 
-                let true_x_z_data = vec![(self.driver.current_position.x as f64, self.driver.current_position.z as f64)];
+                let true_x_z_data = vec![(self.view.position.x as f64, self.view.position.z as f64)];
                 let true_x_z_map = self.make_map(&true_x_z_data, String::from("True X-Z"), [0.0, 2.0], [-2.0, 2.0]);
                 rect.render_widget(true_x_z_map, middle_right_bottom_chunks[0]);
 
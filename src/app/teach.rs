@@ -0,0 +1,198 @@
+use crate::app::App;
+use crate::program::{MoveKind, Program, ProgramError, Replay, ReplayState, Waypoint};
+
+pub const DEFAULT_PROGRAM_PATH: &str = "./program.txt";
+
+impl App {
+    // the buffer is `[name] [joint|linear|smooth] [speed]`, any of them can be left out. the view only has the
+    // angles a move started from, so nothing is recorded until the arm has stopped
+    pub fn record_waypoint(&mut self) {
+        if self.executor.is_busy() {
+            self.command_output.insert(String::from("busy moving, record once the arm has stopped"));
+            return
+        }
+
+        let mut name = String::new();
+        let mut kind = MoveKind::Joint;
+        let mut speed = 100.0;
+
+        for word in self.buffer.split_whitespace() {
+            if let Some(x) = MoveKind::parse(word) {
+                kind = x;
+            } else if let Ok(x) = word.trim_end_matches('%').parse::<f32>() {
+                if x <= 0.0 || x > 100.0 {
+                    self.command_output.insert(format!("speed {} has to be above 0 and at most 100", x));
+                    return
+                }
+
+                speed = x;
+            } else {
+                name = word.to_string();
+            }
+        }
+
+        if self.program.has_waypoint(&name) {
+            self.command_output.insert(format!("there's already a waypoint called {}, pick another name", name));
+            return
+        }
+
+        let name = self.program.get_free_name(&name);
+        let waypoint = Waypoint { name, kind, speed, angles: self.view.angles.clone() };
+
+        self.command_output.insert(format!("recorded {}", waypoint.to_line()));
+        self.program.waypoints.push(waypoint);
+    }
+
+    pub fn remove_last_waypoint(&mut self) {
+        match self.program.waypoints.pop() {
+            Some(x) => self.command_output.insert(format!("removed {}", x.name)),
+            None => self.command_output.insert(String::from("the program is empty"))
+        }
+    }
+
+    pub fn save_program(&mut self, path: String) {
+        match self.program.save(&path) {
+            Ok(x) => self.command_output.insert(x),
+            Err(e) => self.command_output.insert(format!("unable to save program: {}", e))
+        }
+    }
+
+    pub fn load_program(&mut self, path: String) {
+        match Program::load(&path) {
+            Ok(x) => {
+                self.command_output.insert(format!("loaded {} waypoints from {}", x.waypoints.len(), path));
+                self.program = x;
+                self.replay = None;
+            },
+
+            Err(e) => self.command_output.insert(e.get_message())
+        }
+    }
+
+    // the buffer holds the loop count, 0 loops until stopped and blank runs once
+    pub fn start_replay(&mut self) {
+        let loops = match self.buffer.trim() {
+            "" => 1,
+            x => match x.parse::<u32>() {
+                Ok(x) => x,
+                Err(_) => {
+                    self.command_output.insert(format!("'{}' is not a loop count", x));
+                    return
+                }
+            }
+        };
+
+        if self.program.waypoints.is_empty() {
+            self.command_output.insert(String::from("nothing to replay, record some waypoints first"));
+            return
+        }
+
+        let replay = Replay::new(self.program.clone(), loops);
+        self.command_output.insert(format!("replaying {}", replay.get_status()));
+        self.replay = Some(replay);
+    }
+
+    // queues one waypoint and leaves the replay paused, starting one if there isn't any
+    pub fn step_replay(&mut self) {
+        if self.replay.as_ref().is_none_or(|x| x.state == ReplayState::Done) {
+            if self.program.waypoints.is_empty() {
+                self.command_output.insert(String::from("nothing to replay, record some waypoints first"));
+                return
+            }
+
+            self.replay = Some(Replay::new(self.program.clone(), 1));
+        }
+
+        if let Some(replay) = self.replay.as_mut() {
+            replay.state = ReplayState::Paused;
+        }
+
+        if self.replay_job.is_some() {
+            self.command_output.insert(String::from("still moving to the last waypoint"));
+            return
+        }
+
+        self.advance_replay();
+    }
+
+    pub fn toggle_replay_pause(&mut self) {
+        match self.replay.as_mut() {
+            Some(replay) if replay.state != ReplayState::Done => {
+                replay.toggle_pause();
+                let status = replay.get_status();
+                self.command_output.insert(status);
+            },
+
+            _ => self.command_output.insert(String::from("no replay running"))
+        }
+    }
+
+    // whatever waypoint is moving finishes, cancel with space to stop it too
+    pub fn stop_replay(&mut self) {
+        self.replay_job = None;
+
+        if self.replay.take().is_some() {
+            self.command_output.insert(String::from("replay stopped"));
+        }
+    }
+
+    // the next waypoint goes in once the last one and anything else queued have finished
+    pub fn tick_replay(&mut self) {
+        if self.replay_job.is_some() || self.executor.is_busy() { return }
+
+        if self.replay.as_ref().is_some_and(|x| x.state == ReplayState::Running) {
+            self.advance_replay();
+        }
+    }
+
+    fn advance_replay(&mut self) {
+        let waypoint = match self.replay.as_mut().and_then(|x| x.get_next()) {
+            Some(x) => x,
+            None => return
+        };
+
+        self.add_current_position();
+
+        let id = self.executor.submit(Box::new(move |driver| {
+            match waypoint.execute(driver) {
+                Ok(()) => Ok(format!("{} {} at {}%", waypoint.kind.get_name(), waypoint.name, waypoint.speed)),
+                Err(e) => Err(ProgramError::Driver(waypoint.name.clone(), e).get_message())
+            }
+        }));
+
+        self.replay_job = Some(id);
+    }
+
+    // called by poll_jobs when the waypoint advance_replay queued has finished
+    pub fn finish_replay_step(&mut self, result: Result<String, String>) {
+        self.replay_job = None;
+
+        let replay = match self.replay.as_mut() {
+            Some(x) => x,
+            None => return
+        };
+
+        replay.advance(result.is_ok());
+        let done = replay.state == ReplayState::Done;
+
+        match result {
+            Ok(x) => self.command_output.insert(x),
+            Err(e) => self.command_output.insert(format!("{}, replay paused", e))
+        }
+
+        if done { self.command_output.insert(String::from("replay finished")) }
+    }
+
+    pub fn get_replay_status(&self) -> String {
+        return match self.replay.as_ref() {
+            Some(x) => x.get_status(),
+            None => String::from("stopped")
+        }
+    }
+
+    pub fn get_program_path(&self) -> String {
+        let path = self.buffer.trim();
+
+        return if path.is_empty() { String::from(DEFAULT_PROGRAM_PATH) } else { path.to_string() }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::driver::Driver;
+use crate::utils::{Point, ArmPose, AngleSet};
+
+// what the interface draws of the driver, copied whenever the executor isn't holding it so a long move
+// doesn't freeze the screen. while something is moving this is where it was when the move started
+pub struct DriverView {
+    pub position: Point,
+    pub pose: ArmPose,
+    pub angles: AngleSet,
+    pub column_angle: f32, // degrees, for the top bar
+    pub beam_angle: f32,
+    pub base_angle: f32,
+    pub homed: bool,
+    pub has_home_switches: bool,
+    pub info: Vec<(String, String)>
+}
+
+impl DriverView {
+    pub fn new(driver: &Driver) -> DriverView {
+        return DriverView {
+            position: driver.current_position.clone(),
+            pose: driver.get_pose(),
+            angles: driver.get_angle_set(),
+            column_angle: driver.get_column_angle(),
+            beam_angle: driver.get_beam_angle(),
+            base_angle: driver.get_base_angle(),
+            homed: driver.homed,
+            has_home_switches: driver.has_home_switches(),
+            info: DriverView::get_info(driver)
+        }
+    }
+
+    fn get_info(driver: &Driver) -> Vec<(String, String)> {
+        let beam = driver.get_beam_position();
+        let column = driver.get_column_position();
+
+        let info = vec![
+            ("DELAY: ", format!("{}", driver.micro_delay_default)),
+            ("MAX DELAY: ", format!("{}", driver.micro_delay_max)),
+            ("MIN DELAY: ", format!("{}", driver.micro_delay_min)),
+            ("MVNT AMT: ", format!("{}", driver.movement_amount)),
+            ("BEAM X: ", format!("{}", beam.x)),
+            ("BEAM Y: ", format!("{}", beam.y)),
+            ("COLUMN X: ", format!("{}", column.x)),
+            ("COLUMN Y: ", format!("{}", column.y)),
+            ("STEP AMT: ", format!("{}", driver.generic_step_amount)),
+            ("STEPS/DEG: ", format!("{:.2} {:.2} {:.2}", 1.0/driver.column_step_degree, 1.0/driver.beam_step_degree,
+                                    1.0/driver.base_step_degree)),
+            ("COLUMN LIM: ", format!("{} {}", driver.column_limits.min, driver.column_limits.max)),
+            ("BEAM LIM: ", format!("{} {}", driver.beam_limits.min, driver.beam_limits.max)),
+            ("BASE LIM: ", format!("{} {}", driver.base_limits.min, driver.base_limits.max)),
+            ("HOMED: ", String::from(if driver.homed { "yes" } else { "no" })),
+            ("ELBOW: ", String::from(driver.elbow_policy.get_name())),
+            ("PROFILE: ", format!("{} {}deg/s {}deg/s2", driver.profile.get_name(), driver.max_velocity, driver.acceleration)),
+            ("TOOL SPEED: ", format!("{}/s", driver.tool_speed)),
+            ("COORDINATED: ", String::from(if driver.coordinated { "yes" } else { "no" })),
+            ("MOTORS: ", driver.describe_motors()),
+        ];
+
+        return info.into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+    }
+}
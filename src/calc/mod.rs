@@ -1,21 +1,46 @@
 use std::f32::consts::PI;
-use crate::utils::{ Point, AngleSet };
-use std::io::prelude::*;
+use crate::utils::{ Point, AngleSet, ArmPose };
+
+#[cfg(test)]
+mod tests;
+
+const REACH_TOLERANCE: f32 = 1e-4;
+
+#[derive(Clone)]
+pub struct IkSolutions {
+    pub elbow_up: AngleSet,
+    pub elbow_down: AngleSet
+}
 
 pub struct Calc {
     pub origin: Point,
-    pub radius: f32
+    pub column_length: f32,
+    pub beam_length: f32
 }
 
 impl Calc {
-    pub fn new(origin_x: f32, origin_y: f32, radius: f32) -> Calc {
+    pub fn new(origin_x: f32, origin_y: f32, column_length: f32, beam_length: f32) -> Calc {
         let origin = Point {
             x: origin_x,
             y: origin_y,
             z: 0.0
         };
 
-        return Calc { origin, radius }
+        return Calc { origin, column_length, beam_length }
+    }
+
+    pub fn min_reach(&self) -> f32 {
+        return f32::abs(self.column_length - self.beam_length)
+    }
+
+    pub fn max_reach(&self) -> f32 {
+        return self.column_length + self.beam_length
+    }
+
+    pub fn is_reachable(&self, x: f32, y: f32) -> bool {
+        let d = Calc::dist(self.origin.x, self.origin.y, x, y);
+
+        return d >= self.min_reach() - REACH_TOLERANCE && d <= self.max_reach() + REACH_TOLERANCE
     }
 
     pub fn dist_3d(start: &Point, end: &Point) -> f32 {
@@ -30,39 +55,89 @@ impl Calc {
         return d;
     }
 
+    #[cfg(test)]
     pub fn get_angles(&self, x: f32, y: f32) -> AngleSet {
+        return self.get_angle_solutions(x, y).elbow_up
+    }
+
+    pub fn get_angle_solutions(&self, x: f32, y: f32) -> IkSolutions {
         let change_x = x - self.origin.x;
         let chang_y = y - self.origin.y;
 
         let d = Calc::dist(self.origin.x, self.origin.y, x, y);
-        let a = d/2.0;
-        let h = f32::sqrt(self.radius.powi(2) - a.powi(2));
-        let i = f32::atan(chang_y/change_x);
+        let i = f32::atan2(chang_y, change_x);
+
+        // law of cosines for the angle between the column and the line to the target,
+        // clamped so rounding at the edges of the annulus doesn't give nan
+        let cos_column = (self.column_length.powi(2) + d.powi(2) - self.beam_length.powi(2)) / (2.0*self.column_length*d);
+        let offset = if d == 0.0 { PI/2.0 } else { f32::acos(f32::clamp(cos_column, -1.0, 1.0)) };
 
-        let column_angle = f32::atan(h/a) + i;
+        let left = self.solve_with_column(i + offset, x, y);
+        let right = self.solve_with_column(i - offset, x, y);
 
-        let point_one = Calc::get_point_2d(column_angle, &self.origin);
+        // the elbow sits on the left of the line to the target when the offset is added,
+        // which is above it for targets in front of the base and below it for targets behind
+        if change_x >= 0.0 {
+            return IkSolutions { elbow_up: left, elbow_down: right }
+        }
+
+        return IkSolutions { elbow_up: right, elbow_down: left }
+    }
+
+    fn solve_with_column(&self, column_angle: f32, x: f32, y: f32) -> AngleSet {
+        let point_one = Calc::get_point_2d(column_angle, self.column_length, &self.origin);
         let change_x2 = x-point_one.x;
         let change_y2 = y-point_one.y;
-        let is_third = i32::abs(i32::signum(f32::signum(change_x2) as i32 + f32::signum(change_y2) as i32 + 2)-1);
 
-        let beam_angle = f32::atan(change_y2/change_x2) - (PI*is_third as f32);
+        let beam_angle = f32::atan2(change_y2, change_x2);
 
         return AngleSet { column_angle, beam_angle, base_angle: 0.0 };
     }
 
+    #[cfg(test)]
     pub fn get_angles_3d(&self, x: f32, y: f32, z: f32) -> AngleSet {
-        let theta = f32::atan(z/x);
+        return self.get_angle_solutions_3d(x, y, z).elbow_up
+    }
 
-        let x_prime = x*f32::cos(-theta) + z*f32::sin(-theta);
+    pub fn get_angle_solutions_3d(&self, x: f32, y: f32, z: f32) -> IkSolutions {
+        let (planar, theta) = Calc::to_plane(x, y, z);
 
-        let x_y_angles = self.get_angles(x_prime, y);
+        let mut solutions = self.get_angle_solutions(planar.x, planar.y);
+        solutions.elbow_up.base_angle = theta;
+        solutions.elbow_down.base_angle = theta;
 
-        return AngleSet {
-            column_angle: x_y_angles.column_angle,
-            beam_angle: x_y_angles.beam_angle,
-            base_angle: theta
+        return solutions
+    }
+
+    // the point in the arm's plane and the base angle that put the end effector at x y z.
+    // base stays within +-90 degrees, targets behind it are reached with a negative x_prime
+    pub fn to_plane(x: f32, y: f32, z: f32) -> (Point, f32) {
+        let theta = if x != 0.0 { f32::atan(z/x) }
+                    else if z != 0.0 { f32::signum(z) * PI/2.0 }
+                    else { 0.0 };
+
+        let x_prime = x*f32::cos(theta) + z*f32::sin(theta);
+
+        return (Point { x: x_prime, y, z: 0.0 }, theta)
+    }
+
+    // to_plane with the base on whichever of its two answers is the shorter turn from `base`. the other one
+    // faces the opposite way and reaches back over the top with x_prime negated
+    pub fn to_plane_near(x: f32, y: f32, z: f32, base: f32) -> (Point, f32) {
+        let (planar, theta) = Calc::to_plane(x, y, z);
+        let flipped = Calc::wrap_radian(theta + PI);
+
+        if (flipped - base).abs() < (theta - base).abs() {
+            return (Point { x: -planar.x, y, z: 0.0 }, flipped)
         }
+
+        return (planar, theta)
+    }
+
+    pub fn is_reachable_3d(&self, x: f32, y: f32, z: f32) -> bool {
+        let (planar, _) = Calc::to_plane(x, y, z);
+
+        return self.is_reachable(planar.x, planar.y)
     }
 
     pub fn to_degree(angle: f32) -> f32 {
@@ -73,20 +148,130 @@ impl Calc {
         return (PI*angle) / (180.0);
     }
 
-    pub fn get_point_2d(angle: f32, center: &Point) -> Point {
-        let x = f32::cos(angle) + center.x;
-        let y = f32::sin(angle) + center.y;
+    // radians into (-pi, pi], the same direction the short way round. angles already in there come back untouched
+    pub fn wrap_radian(angle: f32) -> f32 {
+        if angle > -PI && angle <= PI { return angle }
+
+        let wrapped = (angle + PI).rem_euclid(2.0*PI) - PI;
+
+        return if wrapped <= -PI { PI } else { wrapped }
+    }
+
+    // evenly spaced points no further than max_length apart, leaving out start and ending on end
+    pub fn get_line_points(start: &Point, end: &Point, max_length: f32) -> Vec<Point> {
+        let count = f32::max(1.0, f32::ceil(Calc::dist_3d(start, end) / max_length)) as usize;
+
+        return (1..=count).map(|i| {
+            let t = i as f32 / count as f32;
+
+            Point { x: start.x + (end.x - start.x) * t, y: start.y + (end.y - start.y) * t, z: start.z + (end.z - start.z) * t }
+        }).collect()
+    }
+
+    // points along an arc around center from start to end, no further than max_length apart. a start and
+    // end on the same spot make a full circle, different radii at each end spiral from one to the other
+    pub fn get_arc_points(start: &Point, center: &Point, end: &Point, clockwise: bool, max_length: f32) -> Vec<Point> {
+        let start_radius = Calc::dist(center.x, center.y, start.x, start.y);
+        let end_radius = Calc::dist(center.x, center.y, end.x, end.y);
+        let start_angle = f32::atan2(start.y - center.y, start.x - center.x);
+        let end_angle = f32::atan2(end.y - center.y, end.x - center.x);
+
+        let mut sweep = if clockwise { (start_angle - end_angle).rem_euclid(2.0 * PI) }
+                        else { (end_angle - start_angle).rem_euclid(2.0 * PI) };
+
+        if sweep < REACH_TOLERANCE { sweep = 2.0 * PI }
+        if clockwise { sweep = -sweep }
+
+        let length = f32::abs(sweep) * f32::max(start_radius, end_radius);
+        let count = f32::max(1.0, f32::ceil(length / max_length)) as usize;
+
+        let mut points: Vec<Point> = (1..count).map(|i| {
+            let t = i as f32 / count as f32;
+            let mut point = Calc::get_point_2d(start_angle + sweep * t, start_radius + (end_radius - start_radius) * t, center);
+            point.z = start.z + (end.z - start.z) * t;
+
+            point
+        }).collect();
+
+        points.push(end.clone());
+
+        return points
+    }
+
+    // the circle through all three points, None when they're on a line
+    pub fn get_arc_center(a: &Point, b: &Point, c: &Point) -> Option<Point> {
+        let d = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+
+        if d.abs() < REACH_TOLERANCE { return None }
+
+        let (a2, b2, c2) = (a.x * a.x + a.y * a.y, b.x * b.x + b.y * b.y, c.x * c.x + c.y * c.y);
+
+        let x = (a2 * (b.y - c.y) + b2 * (c.y - a.y) + c2 * (a.y - b.y)) / d;
+        let y = (a2 * (c.x - b.x) + b2 * (a.x - c.x) + c2 * (b.x - a.x)) / d;
+
+        return Some(Point { x, y, z: 0.0 })
+    }
+
+    // going a, b, c turns clockwise
+    pub fn is_clockwise(a: &Point, b: &Point, c: &Point) -> bool {
+        return (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x) < 0.0
+    }
+
+    // g-code style radius arcs, a positive radius takes the short way round and a negative one the long way.
+    // None when the ends are further apart than the diameter
+    pub fn get_radius_center(start: &Point, end: &Point, radius: f32, clockwise: bool) -> Option<Point> {
+        let chord = Calc::dist(start.x, start.y, end.x, end.y);
+        let half = chord / 2.0;
+
+        if chord < REACH_TOLERANCE || half > radius.abs() + REACH_TOLERANCE { return None }
+
+        let height = f32::sqrt(f32::max(0.0, radius * radius - half * half));
+        let (mid_x, mid_y) = ((start.x + end.x) / 2.0, (start.y + end.y) / 2.0);
+
+        // unit normal to the chord, on the left going from start to end
+        let (normal_x, normal_y) = (-(end.y - start.y) / chord, (end.x - start.x) / chord);
+
+        // the short arc's center is on the right going clockwise and on the left going counter clockwise
+        let side = if clockwise == (radius > 0.0) { -1.0 } else { 1.0 };
+
+        return Some(Point { x: mid_x + normal_x * height * side, y: mid_y + normal_y * height * side, z: 0.0 })
+    }
+
+    pub fn get_point_2d(angle: f32, radius: f32, center: &Point) -> Point {
+        let x = radius*f32::cos(angle) + center.x;
+        let y = radius*f32::sin(angle) + center.y;
 
         return Point{ x, y, z: 0.0 }
      }
 
-    pub fn snap(angle: f32, precision: f32) -> f32{
-        return f32::round(angle / precision) * precision;
+    // swings a point in the arm plane around the vertical axis through the origin
+    pub fn rotate_about_base(point: &Point, base_angle: f32, origin: &Point) -> Point {
+        let reach = point.x - origin.x;
+
+        return Point {
+            x: origin.x + reach*f32::cos(base_angle),
+            y: point.y,
+            z: origin.z + reach*f32::sin(base_angle)
+        }
+    }
+
+    // angles in radians, beam_angle is measured from the x axis like get_angles returns it
+    pub fn forward_kinematics(angles: &AngleSet, origin: &Point, column_length: f32, beam_length: f32) -> ArmPose {
+        let elbow = Calc::get_point_2d(angles.column_angle, column_length, origin);
+        let end_effector = Calc::get_point_2d(angles.beam_angle, beam_length, &elbow);
+
+        return ArmPose {
+            base: origin.clone(),
+            elbow: Calc::rotate_about_base(&elbow, angles.base_angle, origin),
+            end_effector: Calc::rotate_about_base(&end_effector, angles.base_angle, origin)
+        }
+    }
+
+    pub fn get_pose(&self, angles: &AngleSet) -> ArmPose {
+        return Calc::forward_kinematics(angles, &self.origin, self.column_length, self.beam_length)
     }
 
     pub fn smooth(points: Vec<i64>) -> Vec<i64> {
-        let amp = 1;
-        let min = points[0] as f64;
         let max = (points[points.len() - 1] + 1) as f64;
 
         let mut smoothed: Vec<i64> = Vec::new();
@@ -114,7 +299,7 @@ impl Calc {
     }
 
     pub fn normalize_vec(start: i64, end: i64, input: Vec<i64>) -> Option<Vec<i64>> {
-        if input.len() == 0 { return None }
+        if input.is_empty() { return None }
         let mut new = input.clone();
 
         let min = input.iter().min().unwrap();
@@ -127,24 +312,4 @@ impl Calc {
 
         return Some(new);
     }
-
-    pub fn test_temp() {
-        let steps = 1000;
-        let mut counter = 1;
-        let mut times: Vec<i64> = Vec::new();
-
-        (0..i32::abs(steps)).for_each(|_| { times.push(counter); counter += 1});
-
-        let smoothed = Calc::smooth(times);
-        let mut file = std::fs::File::create("temp").unwrap();
-
-        let start = 1500;
-        let end = 4000;
-            
-        let normalized = Calc::normalize_vec(start, end, smoothed.clone()).unwrap();
-
-        for item in normalized {
-            file.write_all(format!("{} ", item).as_bytes()).unwrap();
-        }
-    }
 }
@@ -0,0 +1,301 @@
+use super::*;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+const TOLERANCE: f32 = 1e-3;
+
+// end effector in the arm plane, ignoring the base rotation
+fn forward(calc: &Calc, angles: &AngleSet) -> Point {
+    let planar = AngleSet { base_angle: 0.0, ..angles.clone() };
+
+    return calc.get_pose(&planar).end_effector
+}
+
+fn assert_reaches(calc: &Calc, x: f32, y: f32) {
+    let angles = calc.get_angles(x, y);
+    let end = forward(calc, &angles);
+
+    assert!(angles.column_angle.is_finite() && angles.beam_angle.is_finite(), "nan angles for {} {}", x, y);
+    assert!(Calc::dist(end.x, end.y, x, y) < TOLERANCE, "target {} {} solved to {} {}", x, y, end.x, end.y);
+}
+
+#[test]
+fn get_point_2d_is_on_unit_circle() {
+    let center = Point { x: 0.5, y: -0.25, z: 0.0 };
+
+    for i in 0..16 {
+        let angle = i as f32 * PI / 8.0;
+        let point = Calc::get_point_2d(angle, 1.0, &center);
+
+        assert!((Calc::dist(center.x, center.y, point.x, point.y) - 1.0).abs() < 1e-6);
+        assert!((f32::atan2(point.y - center.y, point.x - center.x) - f32::atan2(angle.sin(), angle.cos())).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn round_trips_over_reachable_annulus() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 1.0);
+    let mut rng = StdRng::seed_from_u64(11);
+
+    for _ in 0..2000 {
+        let d = rng.gen_range(0.01..=2.0);
+        let theta = rng.gen_range(-PI..PI);
+
+        assert_reaches(&calc, d * theta.cos(), d * theta.sin());
+    }
+}
+
+#[test]
+fn round_trips_negative_x_targets() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 1.0);
+
+    for (x, y) in [(-1.0, 1.0), (-1.5, 0.2), (-0.3, 0.1), (-1.0, -1.0), (-0.5, -1.2), (-1.9, -0.1)] {
+        assert_reaches(&calc, x, y);
+    }
+}
+
+#[test]
+fn round_trips_points_on_axes() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 1.0);
+
+    for d in [0.1, 0.5, 1.0, 1.5, 1.99] {
+        assert_reaches(&calc, d, 0.0);
+        assert_reaches(&calc, -d, 0.0);
+        assert_reaches(&calc, 0.0, d);
+        assert_reaches(&calc, 0.0, -d);
+    }
+}
+
+#[test]
+fn round_trips_full_reach_boundary() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 1.0);
+
+    for i in 0..32 {
+        let theta = i as f32 * PI / 16.0;
+        let angles = calc.get_angles(2.0 * theta.cos(), 2.0 * theta.sin());
+
+        assert_reaches(&calc, 2.0 * theta.cos(), 2.0 * theta.sin());
+        // fully stretched, both links point the same way
+        assert!((angles.column_angle - angles.beam_angle).sin().abs() < 1e-2);
+    }
+}
+
+#[test]
+fn round_trips_with_offset_origin() {
+    let calc = Calc::new(0.5, -0.5, 1.0, 1.0);
+    let mut rng = StdRng::seed_from_u64(7);
+
+    for _ in 0..500 {
+        let d = rng.gen_range(0.01..=2.0);
+        let theta = rng.gen_range(-PI..PI);
+
+        assert_reaches(&calc, 0.5 + d * theta.cos(), -0.5 + d * theta.sin());
+    }
+}
+
+#[test]
+fn get_angles_3d_matches_base_rotation() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 1.0);
+    let mut rng = StdRng::seed_from_u64(3);
+
+    for _ in 0..1000 {
+        let x = rng.gen_range(-1.4..1.4);
+        let y = rng.gen_range(-1.4..1.4);
+        let z = rng.gen_range(-1.4..1.4);
+
+        if Calc::dist_3d(&calc.origin, &Point { x, y, z }) > 2.0 || f32::hypot(x, y) < 0.01 { continue }
+
+        let angles = calc.get_angles_3d(x, y, z);
+        let planar = forward(&calc, &angles);
+
+        // the planar solution is the distance out along the base heading
+        assert!((planar.x * angles.base_angle.cos() - x).abs() < TOLERANCE, "x for {} {} {}", x, y, z);
+        assert!((planar.x * angles.base_angle.sin() - z).abs() < TOLERANCE, "z for {} {} {}", x, y, z);
+        assert!((planar.y - y).abs() < TOLERANCE, "y for {} {} {}", x, y, z);
+    }
+}
+
+#[test]
+fn get_angles_3d_on_axes() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 1.0);
+
+    let flat = calc.get_angles_3d(1.0, 1.0, 0.0);
+    assert_eq!(flat.base_angle, 0.0);
+
+    let side = calc.get_angles_3d(0.0, 1.0, 1.0);
+    assert!((side.base_angle - PI / 2.0).abs() < 1e-6);
+    assert!((forward(&calc, &side).x - 1.0).abs() < TOLERANCE);
+
+    let other_side = calc.get_angles_3d(0.0, 1.0, -1.0);
+    assert!((other_side.base_angle + PI / 2.0).abs() < 1e-6);
+    assert!((forward(&calc, &other_side).x - 1.0).abs() < TOLERANCE);
+}
+
+#[test]
+fn forward_kinematics_uses_link_lengths() {
+    let origin = Point { x: 0.0, y: 0.0, z: 0.0 };
+    let angles = AngleSet { column_angle: PI / 2.0, beam_angle: 0.0, base_angle: 0.0 };
+
+    let pose = Calc::forward_kinematics(&angles, &origin, 2.0, 0.5);
+
+    assert!((pose.elbow.x - 0.0).abs() < 1e-6 && (pose.elbow.y - 2.0).abs() < 1e-6);
+    assert!((pose.end_effector.x - 0.5).abs() < 1e-6 && (pose.end_effector.y - 2.0).abs() < 1e-6);
+    assert_eq!(pose.end_effector.z, 0.0);
+}
+
+#[test]
+fn forward_kinematics_rotates_about_base() {
+    let origin = Point { x: 0.0, y: 0.0, z: 0.0 };
+    let angles = AngleSet { column_angle: PI / 2.0, beam_angle: 0.0, base_angle: PI / 2.0 };
+
+    let pose = Calc::forward_kinematics(&angles, &origin, 1.0, 1.0);
+
+    // straight up is on the axis of rotation
+    assert!(pose.elbow.x.abs() < 1e-6 && pose.elbow.z.abs() < 1e-6 && (pose.elbow.y - 1.0).abs() < 1e-6);
+    assert!(pose.end_effector.x.abs() < 1e-6 && (pose.end_effector.z - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn forward_kinematics_inverts_get_angles_3d() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 1.0);
+
+    for (x, y, z) in [(1.0, 0.5, 0.5), (-0.8, 1.0, 0.3), (0.2, -0.4, -1.1), (0.0, 1.0, 1.0)] {
+        let pose = calc.get_pose(&calc.get_angles_3d(x, y, z));
+
+        assert!(Calc::dist_3d(&pose.end_effector, &Point { x, y, z }) < TOLERANCE, "{} {} {}", x, y, z);
+    }
+}
+
+#[test]
+fn round_trips_unequal_links() {
+    for (column_length, beam_length) in [(1.0, 0.6), (0.7, 1.3)] {
+        let calc = Calc::new(0.0, 0.0, column_length, beam_length);
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..1000 {
+            let d = rng.gen_range(calc.min_reach()..=calc.max_reach());
+            let theta = rng.gen_range(-PI..PI);
+
+            assert_reaches(&calc, d * theta.cos(), d * theta.sin());
+        }
+    }
+}
+
+#[test]
+fn unequal_links_keep_their_lengths() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 0.6);
+    let angles = calc.get_angles(0.9, 0.7);
+    let pose = calc.get_pose(&angles);
+
+    assert!((Calc::dist_3d(&pose.base, &pose.elbow) - 1.0).abs() < 1e-5);
+    assert!((Calc::dist_3d(&pose.elbow, &pose.end_effector) - 0.6).abs() < 1e-5);
+}
+
+#[test]
+fn wrapped_angles_stay_within_half_a_turn() {
+    assert!((Calc::wrap_radian(Calc::to_radian(-213.25)) - Calc::to_radian(146.75)).abs() < 1e-5);
+    assert!((Calc::wrap_radian(Calc::to_radian(278.5)) - Calc::to_radian(-81.5)).abs() < 1e-5);
+    assert_eq!(Calc::wrap_radian(-PI), PI);
+    assert_eq!(Calc::wrap_radian(PI), PI);
+    assert_eq!(Calc::wrap_radian(0.7), 0.7);
+}
+
+#[test]
+fn reach_is_the_annulus() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 0.6);
+
+    assert!(calc.is_reachable(1.6, 0.0));
+    assert!(calc.is_reachable(0.0, -0.4));
+    assert!(calc.is_reachable(-1.0, 0.0));
+    assert!(!calc.is_reachable(1.61, 0.0));
+    assert!(!calc.is_reachable(0.2, 0.1));
+    assert!(!calc.is_reachable(0.0, 0.0));
+}
+
+#[test]
+fn both_elbow_solutions_reach_target() {
+    let calc = Calc::new(0.0, 0.0, 1.0, 0.8);
+    let mut rng = StdRng::seed_from_u64(13);
+
+    for _ in 0..500 {
+        let d = rng.gen_range(calc.min_reach() + 0.01..calc.max_reach() - 0.01);
+        let theta = rng.gen_range(-PI..PI);
+        let (x, y) = (d * theta.cos(), d * theta.sin());
+
+        let solutions = calc.get_angle_solutions(x, y);
+
+        for angles in [&solutions.elbow_up, &solutions.elbow_down] {
+            let end = forward(&calc, angles);
+            assert!(Calc::dist(end.x, end.y, x, y) < TOLERANCE);
+        }
+
+        // elbow up sits above the line from the base to the target
+        let up = calc.get_pose(&solutions.elbow_up).elbow;
+        let down = calc.get_pose(&solutions.elbow_down).elbow;
+        let line_height = |px: f32| if x == 0.0 { 0.0 } else { y * px / x };
+
+        if x.abs() > 0.05 {
+            assert!(up.y - line_height(up.x) >= down.y - line_height(down.x), "{} {}", x, y);
+        }
+    }
+}
+
+#[test]
+fn arc_points_stay_on_the_circle() {
+    let center = Point { x: 1.0, y: 0.5, z: 0.0 };
+    let start = Point { x: 1.5, y: 0.5, z: 0.0 };
+    let end = Point { x: 1.0, y: 1.0, z: 0.0 };
+
+    let ccw = Calc::get_arc_points(&start, &center, &end, false, 0.01);
+    let cw = Calc::get_arc_points(&start, &center, &end, true, 0.01);
+
+    // a quarter turn one way and three quarters the other
+    assert_eq!(ccw.len(), (0.25 * PI / 0.01).ceil() as usize);
+    assert_eq!(cw.len(), (0.75 * PI / 0.01).ceil() as usize);
+    assert!(ccw.iter().chain(cw.iter()).all(|x| (Calc::dist(x.x, x.y, center.x, center.y) - 0.5).abs() < 1e-5));
+    assert!(ccw.iter().all(|x| x.x >= 1.0 - 1e-5 && x.y >= 0.5 - 1e-5));
+    assert!(cw.iter().any(|x| x.y < 0.1));
+
+    let last = cw.last().unwrap();
+    assert_eq!((last.x, last.y), (end.x, end.y));
+}
+
+#[test]
+fn arc_back_to_the_start_is_a_full_circle() {
+    let center = Point { x: 0.0, y: 1.0, z: 0.0 };
+    let start = Point { x: 0.0, y: 1.2, z: 0.0 };
+    let points = Calc::get_arc_points(&start, &center, &start, false, 0.01);
+
+    assert_eq!(points.len(), (0.4 * PI / 0.01).ceil() as usize);
+    assert!(points.iter().any(|x| x.y < 0.81));
+}
+
+#[test]
+fn arc_centers_from_three_points() {
+    let a = Point { x: 1.0, y: 0.0, z: 0.0 };
+    let b = Point { x: 0.0, y: 1.0, z: 0.0 };
+    let c = Point { x: -1.0, y: 0.0, z: 0.0 };
+
+    let center = Calc::get_arc_center(&a, &b, &c).unwrap();
+
+    assert!(Calc::dist(center.x, center.y, 0.0, 0.0) < 1e-5);
+    assert!(!Calc::is_clockwise(&a, &b, &c));
+    assert!(Calc::is_clockwise(&c, &b, &a));
+    assert!(Calc::get_arc_center(&a, &Point { x: 0.0, y: 0.0, z: 0.0 }, &c).is_none());
+}
+
+#[test]
+fn radius_arcs_take_the_short_way_unless_negative() {
+    let start = Point { x: 0.0, y: 0.0, z: 0.0 };
+    let end = Point { x: 2.0, y: 0.0, z: 0.0 };
+
+    // clockwise over the top keeps the center below the chord for the short arc
+    let short = Calc::get_radius_center(&start, &end, 2.0, true).unwrap();
+    let long = Calc::get_radius_center(&start, &end, -2.0, true).unwrap();
+    let ccw = Calc::get_radius_center(&start, &end, 2.0, false).unwrap();
+
+    assert!(short.y < 0.0 && long.y > 0.0 && ccw.y > 0.0);
+    assert!((Calc::dist(short.x, short.y, start.x, start.y) - 2.0).abs() < 1e-5);
+    assert!(Calc::get_arc_points(&start, &short, &end, true, 0.01).iter().all(|x| x.y >= -1e-5));
+    assert!(Calc::get_radius_center(&start, &end, 0.9, true).is_none());
+}
@@ -0,0 +1,477 @@
+use serde::Deserialize;
+
+use crate::driver::{ElbowPolicy, JointLimits};
+use crate::profile::ProfileKind;
+
+pub const DEFAULT_PATH: &str = "./arm.toml";
+
+// steps_per_rev is the bare motor, gear_ratio is motor turns per joint turn.
+// limits are in degrees, the beam's are relative to the column. home_angle is the joint angle
+// where the switch on home_pin closes, home_positive says which way the joint turns to reach it
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AxisConfig {
+    pub direction_pin: u8,
+    pub step_pin: u8,
+    #[serde(default)]
+    pub invert: bool,
+    #[serde(default = "AxisConfig::default_steps_per_rev")]
+    pub steps_per_rev: u32,
+    #[serde(default = "AxisConfig::default_microsteps")]
+    pub microsteps: u32,
+    #[serde(default = "AxisConfig::default_gear_ratio")]
+    pub gear_ratio: f32,
+    #[serde(default = "AxisConfig::default_min_angle")]
+    pub min_angle: f32,
+    #[serde(default = "AxisConfig::default_max_angle")]
+    pub max_angle: f32,
+    #[serde(default)]
+    pub home_pin: Option<u8>,
+    #[serde(default)]
+    pub home_angle: f32,
+    #[serde(default)]
+    pub home_positive: bool
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct MotionConfig {
+    pub delay: i64,
+    pub smooth_min_delay: i64,
+    pub smooth_max_delay: i64,
+    pub delay_limit: i64, // no delay between steps is ever allowed below this
+    pub profile: String,
+    pub max_velocity: f32,
+    pub acceleration: f32,
+    pub jerk: f32,
+    pub coordinated: bool, // joints start and finish every goto together
+    pub tool_speed: f32,
+    pub segment_length: f32
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct HomingConfig {
+    pub required: bool, // refuse to move until homed, only if some axis has a home_pin
+    pub active_low: bool,
+    pub delay: i64,
+    pub backoff_steps: i32,
+    pub search_degrees: f32
+}
+
+// a single output switching whatever is on the end of the arm, no pin means no tool
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolConfig {
+    pub pin: Option<u8>,
+    pub active_low: bool
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct LinkConfig {
+    pub origin_x: f32,
+    pub origin_y: f32,
+    pub column_length: f32,
+    pub beam_length: f32
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    pub movement_amount: f32,
+    pub step_amount: i32,
+    pub elbow: String,
+    pub prev_positions: usize,
+    pub command_output: usize,
+    pub history: String, // where lines entered in buffer mode are kept between runs
+    pub history_size: usize
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub column: AxisConfig,
+    pub beam: AxisConfig,
+    pub base: AxisConfig,
+    pub motion: MotionConfig,
+    pub homing: HomingConfig,
+    pub tool: ToolConfig,
+    pub links: LinkConfig,
+    pub ui: UiConfig
+}
+
+fn is_positive(value: f32) -> bool {
+    return value > 0.0 // false for nan as well
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Missing(String),
+    Unreadable(String, String),
+    Parse(String, String),
+    Invalid(String, Vec<String>)
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self {
+        MotionConfig { delay: 2500, smooth_min_delay: 2500, smooth_max_delay: 4000, delay_limit: 10,
+                       profile: String::from("poly"), max_velocity: 30.0, acceleration: 60.0, jerk: 240.0,
+                       coordinated: true, tool_speed: 0.2, segment_length: 0.01 }
+    }
+}
+
+impl Default for HomingConfig {
+    fn default() -> Self {
+        HomingConfig { required: true, active_low: true, delay: 5000, backoff_steps: 100, search_degrees: 360.0 }
+    }
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        LinkConfig { origin_x: 0.0, origin_y: 0.0, column_length: 1.0, beam_length: 1.0 }
+    }
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        UiConfig { movement_amount: 0.05, step_amount: 400, elbow: String::from("up"), prev_positions: 10,
+                   command_output: 10, history: String::from("./history.txt"), history_size: 200 }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            column: AxisConfig::new(20, 21),
+            beam: AxisConfig::new(7, 8),
+            base: AxisConfig::new(5, 6),
+            motion: MotionConfig::default(),
+            homing: HomingConfig::default(),
+            tool: ToolConfig::default(),
+            links: LinkConfig::default(),
+            ui: UiConfig::default()
+        }
+    }
+}
+
+impl AxisConfig {
+    pub fn new(direction_pin: u8, step_pin: u8) -> AxisConfig {
+        return AxisConfig { direction_pin, step_pin, invert: false, steps_per_rev: AxisConfig::default_steps_per_rev(),
+                            microsteps: AxisConfig::default_microsteps(), gear_ratio: AxisConfig::default_gear_ratio(),
+                            min_angle: AxisConfig::default_min_angle(), max_angle: AxisConfig::default_max_angle(),
+                            home_pin: None, home_angle: 0.0, home_positive: false }
+    }
+
+    fn default_steps_per_rev() -> u32 {
+        return 200
+    }
+
+    fn default_microsteps() -> u32 {
+        return 1
+    }
+
+    fn default_gear_ratio() -> f32 {
+        return 20.0
+    }
+
+    fn default_min_angle() -> f32 {
+        return -180.0
+    }
+
+    fn default_max_angle() -> f32 {
+        return 180.0
+    }
+
+    pub fn get_limits(&self) -> JointLimits {
+        return JointLimits { min: self.min_angle, max: self.max_angle }
+    }
+
+    pub fn get_steps_per_degree(&self) -> f32 {
+        return (self.steps_per_rev * self.microsteps) as f32 * self.gear_ratio / 360.0
+    }
+
+    // joint degrees moved by a single step
+    pub fn get_step_degree(&self) -> f32 {
+        return 1.0 / self.get_steps_per_degree()
+    }
+}
+
+impl ConfigError {
+    pub fn get_messages(&self) -> Vec<String> {
+        match self {
+            ConfigError::Missing(path) => vec![format!("no config at {}", path)],
+
+            ConfigError::Unreadable(path, e) => vec![format!("unable to read config {}: {}", path, e)],
+
+            ConfigError::Parse(path, e) => {
+                // toml errors span several lines, the pane only shows one per entry
+                let mut messages = vec![format!("unable to parse config {}", path)];
+                messages.extend(e.lines().filter(|x| !x.trim().is_empty()).map(String::from));
+
+                messages
+            },
+
+            ConfigError::Invalid(path, errors) => {
+                let mut messages = vec![format!("invalid config {}", path)];
+                messages.extend(errors.iter().cloned());
+
+                messages
+            }
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Config, ConfigError> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(x) => x,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(ConfigError::Missing(path.to_string())),
+            Err(e) => return Err(ConfigError::Unreadable(path.to_string(), format!("{}", e)))
+        };
+
+        return Config::parse(path, &contents)
+    }
+
+    pub fn parse(path: &str, contents: &str) -> Result<Config, ConfigError> {
+        let config: Config = match toml::from_str(contents) {
+            Ok(x) => x,
+            Err(e) => return Err(ConfigError::Parse(path.to_string(), format!("{}", e)))
+        };
+
+        let errors = config.validate();
+
+        if !errors.is_empty() {
+            return Err(ConfigError::Invalid(path.to_string(), errors))
+        }
+
+        Ok(config)
+    }
+
+    pub fn get_axes(&self) -> [(&str, &AxisConfig); 3] {
+        return [("column", &self.column), ("beam", &self.beam), ("base", &self.base)]
+    }
+
+    pub fn get_elbow_policy(&self) -> ElbowPolicy {
+        return ElbowPolicy::parse(&self.ui.elbow).unwrap_or(ElbowPolicy::Up)
+    }
+
+    pub fn get_profile(&self) -> ProfileKind {
+        return ProfileKind::parse(&self.motion.profile).unwrap_or(ProfileKind::Polynomial)
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+        let mut pins: Vec<(u8, String)> = Vec::new();
+
+        for (name, axis) in self.get_axes() {
+            let mut axis_pins = vec![(axis.direction_pin, "direction_pin"), (axis.step_pin, "step_pin")];
+
+            if let Some(pin) = axis.home_pin {
+                axis_pins.push((pin, "home_pin"));
+            }
+
+            for (pin, role) in axis_pins {
+                let label = format!("{}.{}", name, role);
+
+                if pin > 27 {
+                    errors.push(format!("{} = {} is not a gpio pin, expected 0 to 27", label, pin));
+                }
+
+                if let Some((_, other)) = pins.iter().find(|(used, _)| *used == pin) {
+                    errors.push(format!("{} = {} is already used by {}", label, pin, other));
+                }
+
+                pins.push((pin, label));
+            }
+
+            if axis.steps_per_rev == 0 {
+                errors.push(format!("{}.steps_per_rev must be at least 1", name));
+            }
+
+            if axis.microsteps == 0 {
+                errors.push(format!("{}.microsteps must be at least 1", name));
+            }
+
+            if !is_positive(axis.gear_ratio) {
+                errors.push(format!("{}.gear_ratio must be above 0, got {}", name, axis.gear_ratio));
+            }
+
+            if !is_positive(axis.max_angle - axis.min_angle) {
+                errors.push(format!("{}.min_angle = {} must be below {}.max_angle = {}", name, axis.min_angle, name, axis.max_angle));
+            }
+
+            if axis.home_pin.is_some() && !axis.get_limits().contains(axis.home_angle) {
+                errors.push(format!("{}.home_angle = {} is outside the axis limits", name, axis.home_angle));
+            }
+        }
+
+        if let Some(pin) = self.tool.pin {
+            if pin > 27 {
+                errors.push(format!("tool.pin = {} is not a gpio pin, expected 0 to 27", pin));
+            }
+
+            if let Some((_, other)) = pins.iter().find(|(used, _)| *used == pin) {
+                errors.push(format!("tool.pin = {} is already used by {}", pin, other));
+            }
+        }
+
+        let motion = &self.motion;
+
+        if motion.delay_limit < 1 {
+            errors.push(format!("motion.delay_limit must be at least 1, got {}", motion.delay_limit));
+        }
+
+        for (name, delay) in [("delay", motion.delay), ("smooth_min_delay", motion.smooth_min_delay),
+                              ("smooth_max_delay", motion.smooth_max_delay)] {
+            if delay < motion.delay_limit {
+                errors.push(format!("motion.{} = {} is below motion.delay_limit = {}", name, delay, motion.delay_limit));
+            }
+        }
+
+        if ProfileKind::parse(&motion.profile).is_none() {
+            errors.push(format!("motion.profile = {} is not one of poly, trap or scurve", motion.profile));
+        }
+
+        for (name, value) in [("max_velocity", motion.max_velocity), ("acceleration", motion.acceleration),
+                              ("jerk", motion.jerk), ("tool_speed", motion.tool_speed),
+                              ("segment_length", motion.segment_length)] {
+            if !is_positive(value) {
+                errors.push(format!("motion.{} must be above 0, got {}", name, value));
+            }
+        }
+
+        if self.homing.delay < motion.delay_limit {
+            errors.push(format!("homing.delay = {} is below motion.delay_limit = {}", self.homing.delay, motion.delay_limit));
+        }
+
+        if self.homing.backoff_steps < 1 {
+            errors.push(format!("homing.backoff_steps must be at least 1, got {}", self.homing.backoff_steps));
+        }
+
+        if !is_positive(self.homing.search_degrees) {
+            errors.push(format!("homing.search_degrees must be above 0, got {}", self.homing.search_degrees));
+        }
+
+        if motion.smooth_min_delay > motion.smooth_max_delay {
+            errors.push(format!("motion.smooth_min_delay = {} is above motion.smooth_max_delay = {}",
+                                motion.smooth_min_delay, motion.smooth_max_delay));
+        }
+
+        for (name, length) in [("column_length", self.links.column_length), ("beam_length", self.links.beam_length)] {
+            if !is_positive(length) {
+                errors.push(format!("links.{} must be above 0, got {}", name, length));
+            }
+        }
+
+        if !is_positive(self.ui.movement_amount) {
+            errors.push(format!("ui.movement_amount must be above 0, got {}", self.ui.movement_amount));
+        }
+
+        if self.ui.step_amount < 1 {
+            errors.push(format!("ui.step_amount must be at least 1, got {}", self.ui.step_amount));
+        }
+
+        if ElbowPolicy::parse(&self.ui.elbow).is_none() {
+            errors.push(format!("ui.elbow = {} is not one of up, down, near or safe", self.ui.elbow));
+        }
+
+        if self.ui.prev_positions == 0 || self.ui.command_output == 0 {
+            errors.push(String::from("ui.prev_positions and ui.command_output must be at least 1"));
+        }
+
+        if self.ui.history_size == 0 {
+            errors.push(String::from("ui.history_size must be at least 1"));
+        }
+
+        return errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_config_matches_defaults() {
+        let config = Config::parse("arm.toml", include_str!("../arm.toml")).unwrap();
+        let defaults = Config::default();
+
+        assert_eq!(config.base.get_step_degree(), defaults.base.get_step_degree());
+        assert_eq!(config.beam.direction_pin, defaults.beam.direction_pin);
+        assert_eq!(config.motion.smooth_max_delay, defaults.motion.smooth_max_delay);
+        assert_eq!(config.ui.elbow, defaults.ui.elbow);
+        assert_eq!(config.ui.history, defaults.ui.history);
+
+        // no switches or tool until they're wired
+        assert!(config.get_axes().iter().all(|(_, axis)| axis.home_pin.is_none()));
+        assert_eq!(config.homing.required, defaults.homing.required);
+        assert_eq!(config.tool.pin, None);
+    }
+
+    #[test]
+    fn missing_sections_fall_back_to_defaults() {
+        let config = Config::parse("test", "[links]\ncolumn_length = 1.5\n").unwrap();
+
+        assert_eq!(config.links.column_length, 1.5);
+        assert_eq!(config.links.beam_length, 1.0);
+        assert_eq!(config.column.step_pin, 21);
+    }
+
+    #[test]
+    fn reports_every_invalid_value() {
+        let contents = "[beam]\ndirection_pin = 20\nstep_pin = 40\nmicrosteps = 0\n[ui]\nelbow = \"sideways\"\n";
+
+        let errors = match Config::parse("test", contents) {
+            Err(ConfigError::Invalid(_, errors)) => errors,
+            _ => panic!("config should not validate")
+        };
+
+        assert_eq!(errors.len(), 4);
+        assert!(errors.iter().any(|x| x.contains("already used by column.direction_pin")));
+    }
+
+    #[test]
+    fn gearing_is_per_axis() {
+        let contents = "[base]\ndirection_pin = 5\nstep_pin = 6\nsteps_per_rev = 400\nmicrosteps = 8\ngear_ratio = 3.0\n";
+        let config = Config::parse("test", contents).unwrap();
+
+        assert!((config.base.get_steps_per_degree() - 400.0 * 8.0 * 3.0 / 360.0).abs() < 1e-4);
+        // the original arm ran everything at 11.111111 steps a degree
+        assert!((config.column.get_steps_per_degree() - 11.111111).abs() < 1e-4);
+    }
+
+    #[test]
+    fn home_pins_share_the_pin_check() {
+        let contents = "[column]\ndirection_pin = 20\nstep_pin = 21\nhome_pin = 8\nhome_angle = 200.0\n";
+
+        let errors = match Config::parse("test", contents) {
+            Err(ConfigError::Invalid(_, errors)) => errors,
+            _ => panic!("config should not validate")
+        };
+
+        assert!(errors.iter().any(|x| x.contains("already used by column.home_pin")));
+        assert!(errors.iter().any(|x| x.contains("column.home_angle")));
+    }
+
+    #[test]
+    fn profile_comes_from_motion() {
+        let config = Config::parse("test", "[motion]\nprofile = \"scurve\"\njerk = 500.0\n").unwrap();
+
+        assert_eq!(config.get_profile(), ProfileKind::SCurve);
+        assert_eq!(config.motion.jerk, 500.0);
+        assert_eq!(config.motion.max_velocity, 30.0);
+
+        assert!(Config::parse("test", "[motion]\nprofile = \"bumpy\"\nacceleration = 0.0\n").is_err());
+    }
+
+    #[test]
+    fn parse_errors_name_the_file() {
+        let messages = match Config::parse("bench.toml", "[motion]\ndelay = \"fast\"\n") {
+            Err(e) => e.get_messages(),
+            Ok(_) => panic!("config should not parse")
+        };
+
+        assert!(messages[0].contains("bench.toml"));
+    }
+}
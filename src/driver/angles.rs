@@ -0,0 +1,42 @@
+use crate::driver::{Driver, DriverError, MovePlan, PlanTiming};
+use crate::profile::ProfileKind;
+use crate::utils::AngleSet;
+
+// moves straight to a set of joint angles, radians with an absolute beam like everywhere else. all three
+// joints start and finish together and `scale` speeds them up or slows them down, 1.0 being the normal delays
+impl Driver {
+    pub fn goto_angles(&mut self, angles: &AngleSet, scale: f32) -> Result<(), DriverError> {
+        let plan = self.plan_angles(angles, None, scale)?;
+
+        return self.run_plan(&plan)
+    }
+
+    pub fn goto_angles_smooth(&mut self, angles: &AngleSet, profile: ProfileKind, scale: f32) -> Result<(), DriverError> {
+        let plan = self.plan_angles(angles, Some(profile), scale)?;
+
+        return self.run_plan(&plan)
+    }
+
+    // always coordinated, the profile runs on whichever joint has the most steps
+    pub fn plan_angles(&self, angles: &AngleSet, profile: Option<ProfileKind>, scale: f32) -> Result<MovePlan, DriverError> {
+        self.check_homed()?;
+        self.check_limits(angles)?;
+
+        let steps = self.get_steps_3d(angles);
+        let lead = steps.get_lead();
+        let count = steps.get(lead).unsigned_abs();
+
+        let delays = match profile {
+            Some(x) => self.get_profile(x).get_delays(count, self.get_step_degree(lead)),
+            None => vec![self.micro_delay_default; count as usize]
+        };
+
+        let delays = delays.iter().map(|x| self.scale_delay(*x, scale)).collect();
+
+        return Ok(self.plan_steps(steps, PlanTiming::Coordinated(delays)))
+    }
+
+    fn scale_delay(&self, delay: i64, scale: f32) -> i64 {
+        return i64::max((delay as f32 / scale) as i64, self.micro_delay_limit)
+    }
+}
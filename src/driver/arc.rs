@@ -0,0 +1,77 @@
+use crate::calc::Calc;
+use crate::driver::{Driver, DriverError};
+use crate::utils::Point;
+
+// how far apart a center arc's start and end radii can be, as a part of the arm's reach. it covers the
+// few steps the tool is off where it was sent, anything more is a wrong center rather than a circle
+const ARC_RADIUS_TOLERANCE: f32 = 0.0025;
+
+// how the circle an arc runs along is given, in the same plane as the arc's end point
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ArcShape {
+    Center(f32, f32),
+    Radius(f32), // negative for the long way round
+    Through(f32, f32) // the arc passes this point, which also sets the direction
+}
+
+impl Driver {
+    pub fn goto_arc(&mut self, x: f32, y: f32, shape: ArcShape, clockwise: bool) -> Result<(), DriverError> {
+        return self.goto_arc_with(x, y, shape, clockwise, self.tool_speed)
+    }
+
+    // from where the tool is round to x y at `speed` units a second, in the arm's plane like lines
+    pub fn goto_arc_with(&mut self, x: f32, y: f32, shape: ArcShape, clockwise: bool, speed: f32) -> Result<(), DriverError> {
+        let points = self.get_arc_points(&self.get_plane_position(), x, y, shape, clockwise)?;
+        let points: Vec<Point> = points.iter().map(|x| self.to_world(x)).collect();
+
+        return self.follow_path(&points, speed)
+    }
+
+    // the same in the world's x y plane at the z the tool is at, turning the base along the way
+    pub fn goto_arc_3d(&mut self, x: f32, y: f32, shape: ArcShape, clockwise: bool, speed: f32) -> Result<(), DriverError> {
+        let points = self.get_arc_points(&self.current_position.clone(), x, y, shape, clockwise)?;
+
+        return self.follow_path(&points, speed)
+    }
+
+    // from `start` round to x y, keeping start's z
+    pub fn get_arc_points(&self, start: &Point, x: f32, y: f32, shape: ArcShape, clockwise: bool) -> Result<Vec<Point>, DriverError> {
+        let start = start.clone();
+        let end = Point { x, y, z: start.z };
+
+        let (center, clockwise) = match shape {
+            ArcShape::Center(center_x, center_y) => {
+                let start_radius = Calc::dist(center_x, center_y, start.x, start.y);
+                let end_radius = Calc::dist(center_x, center_y, x, y);
+
+                // a spiral between the two is never what was meant, g-code controllers refuse these too
+                if (start_radius - end_radius).abs() > ARC_RADIUS_TOLERANCE * self.calc.max_reach() {
+                    return Err(DriverError::InvalidPath(format!("the arc starts {:.4} from its center and ends {:.4} from it",
+                                                                start_radius, end_radius)))
+                }
+
+                (Point { x: center_x, y: center_y, z: start.z }, clockwise)
+            },
+
+            ArcShape::Radius(radius) => match Calc::get_radius_center(&start, &end, radius, clockwise) {
+                Some(x) => (x, clockwise),
+                None => return Err(DriverError::InvalidPath(format!("no arc of radius {} reaches {} {}", radius, x, y)))
+            },
+
+            ArcShape::Through(via_x, via_y) => {
+                let via = Point { x: via_x, y: via_y, z: 0.0 };
+
+                match Calc::get_arc_center(&start, &via, &end) {
+                    Some(x) => (x, Calc::is_clockwise(&start, &via, &end)),
+                    None => return Err(DriverError::InvalidPath(format!("{} {} is in line with the move, use a line instead", via_x, via_y)))
+                }
+            }
+        };
+
+        if Calc::dist(center.x, center.y, start.x, start.y) < self.segment_length {
+            return Err(DriverError::InvalidPath(String::from("the tool is already at the center of the arc")))
+        }
+
+        return Ok(Calc::get_arc_points(&start, &center, &end, clockwise, self.segment_length))
+    }
+}
@@ -0,0 +1,123 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use crate::driver::{Driver, DriverError};
+
+// shared between the driver and whoever may want to stop it from another thread. the stepping loops
+// check it before every step and count progress into it
+#[derive(Default)]
+pub struct MotionControl {
+    cancel: AtomicBool,
+    stop: AtomicBool,
+    done: AtomicU64,
+    total: AtomicU64,
+    deceleration: AtomicU32 // f32 bits, steps a second squared of the move's lead axis
+}
+
+impl MotionControl {
+    // ramps whatever is moving down to a stop
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::SeqCst);
+    }
+
+    // no more pulses at all, straight away. the tool isn't shared with it, the driver switches that off
+    // once the move sees the stop, or straight away when nothing is moving
+    pub fn emergency_stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+
+    pub fn reset(&self) {
+        self.cancel.store(false, Ordering::SeqCst);
+        self.stop.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        return self.cancel.load(Ordering::SeqCst)
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        return self.stop.load(Ordering::SeqCst)
+    }
+
+    pub fn begin(&self, total: u64, deceleration: f32) {
+        self.done.store(0, Ordering::SeqCst);
+        self.total.store(total, Ordering::SeqCst);
+        self.deceleration.store(deceleration.to_bits(), Ordering::SeqCst);
+    }
+
+    pub fn tick(&self) {
+        self.done.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // steps done and planned for the move running now
+    pub fn get_progress(&self) -> (u64, u64) {
+        return (self.done.load(Ordering::SeqCst), self.total.load(Ordering::SeqCst))
+    }
+
+    pub fn get_deceleration(&self) -> f32 {
+        return f32::from_bits(self.deceleration.load(Ordering::SeqCst))
+    }
+}
+
+// what a stepping loop keeps once it's been cancelled. the step rate falls at the configured acceleration
+// from wherever it was, so the motors slow down instead of stopping dead and losing steps
+#[derive(Default)]
+pub struct Brake {
+    velocity: Option<f32>, // steps a second
+    stopped: bool
+}
+
+impl Brake {
+    // the delay to use for the next step, None once it's time to stop
+    pub fn next_delay(&mut self, control: &MotionControl, delay: i64) -> Option<i64> {
+        if control.is_stopped() { return None }
+        if !control.is_cancelled() { return Some(delay) }
+
+        let velocity = self.velocity.unwrap_or(1_000_000.0 / delay as f32);
+        let squared = velocity * velocity - 2.0 * control.get_deceleration();
+
+        if squared <= 0.0 {
+            self.stopped = true;
+            return None
+        }
+
+        let velocity = f32::sqrt(squared);
+        self.velocity = Some(velocity);
+
+        return Some(i64::max(delay, (1_000_000.0 / velocity) as i64))
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        return self.stopped
+    }
+}
+
+impl Driver {
+    // once a move is planned and before its first step. `total` is what the progress counts up to
+    pub fn begin_move(&self, total: u64, step_degree: f32) -> Result<(), DriverError> {
+        if self.control.is_stopped() { return Err(DriverError::Stopped) }
+        if self.control.is_cancelled() { return Err(DriverError::Cancelled) }
+
+        self.control.begin(total, self.acceleration / step_degree);
+
+        Ok(())
+    }
+
+    // after the steps that were actually taken are counted
+    pub fn end_move(&mut self) -> Result<(), DriverError> {
+        if self.control.is_stopped() {
+            self.mark_stopped();
+            return Err(DriverError::Stopped)
+        }
+
+        if self.control.is_cancelled() { return Err(DriverError::Cancelled) }
+
+        Ok(())
+    }
+
+    // an emergency stop may have lost steps so the position can't be trusted until the arm is homed again,
+    // and the tool goes off with the motors
+    pub fn mark_stopped(&mut self) {
+        self.homed = false;
+        self.switch_tool_off();
+    }
+}
@@ -0,0 +1,145 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::driver::{Driver, DriverError, HomeSwitch, Joint};
+
+impl Driver {
+    pub fn set_home_switch(&mut self, joint: Joint, home: HomeSwitch) {
+        match joint {
+            Joint::Column => self.column_home = Some(home),
+            Joint::Beam => self.beam_home = Some(home),
+            Joint::Base => self.base_home = Some(home)
+        }
+
+        if self.homing.required {
+            self.homed = false;
+        }
+    }
+
+    pub fn get_home(&self, joint: Joint) -> Option<&HomeSwitch> {
+        match joint {
+            Joint::Column => self.column_home.as_ref(),
+            Joint::Beam => self.beam_home.as_ref(),
+            Joint::Base => self.base_home.as_ref()
+        }
+    }
+
+    pub fn has_home_switches(&self) -> bool {
+        return Joint::all().iter().any(|x| self.get_home(*x).is_some())
+    }
+
+    pub fn check_homed(&self) -> Result<(), DriverError> {
+        if !self.homed { return Err(DriverError::NotHomed) }
+
+        Ok(())
+    }
+
+    // homes the column first since the beam rides on it, axes without a switch keep their angle
+    pub fn home(&mut self) -> Result<(), DriverError> {
+        self.homed = !self.homing.required || !self.has_home_switches();
+
+        for joint in Joint::all() {
+            let result = self.home_joint(joint);
+
+            // a stop part way through leaves the arm unhomed
+            self.end_move()?;
+            result?;
+        }
+
+        self.homed = true;
+
+        Ok(())
+    }
+
+    fn home_joint(&mut self, joint: Joint) -> Result<(), DriverError> {
+        let (home_angle, positive) = match self.get_home(joint) {
+            Some(x) => (x.angle, x.positive),
+            None => return Ok(())
+        };
+
+        let step_degree = self.get_step_degree(joint);
+        let max_steps = (self.homing.search_degrees / step_degree) as i32;
+        let backoff_steps = self.homing.backoff_steps;
+
+        // starting on the switch, get off it before looking for the edge
+        if self.is_home_triggered(joint) {
+            let cleared = self.step_until(joint, !positive, max_steps, false)?;
+
+            if cleared.is_none() {
+                return Err(DriverError::HomingFailed(joint, format!("switch still closed after {} steps away from it", max_steps)))
+            }
+
+            self.step_joint(joint, !positive, backoff_steps)?;
+        }
+
+        if self.step_until(joint, positive, max_steps, true)?.is_none() {
+            return Err(DriverError::HomingFailed(joint, format!("switch never closed within {} degrees", self.homing.search_degrees)))
+        }
+
+        self.step_joint(joint, !positive, backoff_steps)?;
+
+        if self.is_home_triggered(joint) {
+            return Err(DriverError::HomingFailed(joint, format!("switch still closed after backing off {} steps", backoff_steps)))
+        }
+
+        let backed_off = backoff_steps as f32 * step_degree;
+        let angle = if positive { home_angle - backed_off } else { home_angle + backed_off };
+
+        self.set_joint_angle(joint, angle);
+
+        Ok(())
+    }
+
+    fn is_home_triggered(&self, joint: Joint) -> bool {
+        return self.get_home(joint).map(|x| x.switch.is_triggered()).unwrap_or(false)
+    }
+
+    // steps the joint one pulse at a time at the homing delay until its switch reads `triggered`,
+    // returns the steps it took or None if it ran out. `dir` is true for positive joint angles
+    fn step_until(&mut self, joint: Joint, dir: bool, max_steps: i32, triggered: bool) -> Result<Option<i32>, DriverError> {
+        for step in 0..max_steps {
+            if self.is_home_triggered(joint) == triggered { return Ok(Some(step)) }
+
+            self.pulse(joint, dir)?;
+        }
+
+        if self.is_home_triggered(joint) == triggered { return Ok(Some(max_steps)) }
+
+        return Ok(None)
+    }
+
+    fn step_joint(&mut self, joint: Joint, dir: bool, steps: i32) -> Result<(), DriverError> {
+        for _ in 0..steps {
+            self.pulse(joint, dir)?;
+        }
+
+        Ok(())
+    }
+
+    // homing is slow enough already that a cancel stops it dead like an emergency stop
+    fn pulse(&mut self, joint: Joint, dir: bool) -> Result<(), DriverError> {
+        if self.control.is_stopped() { return Err(DriverError::Stopped) }
+        if self.control.is_cancelled() { return Err(DriverError::Cancelled) }
+
+        {
+            let mut motor = self.get_motor(joint).lock().unwrap();
+
+            motor.step(!dir);
+            thread::sleep(Duration::from_micros(self.homing.delay as u64));
+            motor.reset();
+            thread::sleep(Duration::from_micros(10));
+        }
+
+        self.count_steps(joint, 1, dir);
+
+        Ok(())
+    }
+
+    // angle is in degrees and relative to the column for the beam, like the joint sees it.
+    // the beam counter is already relative so moving the column's leaves it alone
+    fn set_joint_angle(&mut self, joint: Joint, angle: f32) {
+        let steps = self.get_target_steps(joint, angle);
+
+        self.set_step_count(joint, steps);
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use crate::calc::{Calc, IkSolutions};
+use crate::driver::{Driver, DriverError, Joint};
+use crate::utils::{AngleSet, Point};
+
+// one piece of a streamed path, signed joint steps and the delay between steps of whichever moves most
+pub struct PathSegment {
+    pub column_steps: i32,
+    pub beam_steps: i32,
+    pub base_steps: i32,
+    pub delay: i64
+}
+
+impl PathSegment {
+    pub fn get_lead(&self) -> i32 {
+        return i32::max(i32::max(self.column_steps.abs(), self.beam_steps.abs()), self.base_steps.abs())
+    }
+}
+
+impl Driver {
+    pub fn goto_line(&mut self, x: f32, y: f32) -> Result<(), DriverError> {
+        return self.goto_line_with(x, y, self.tool_speed)
+    }
+
+    // straight from where the tool is to x y with it moving at `speed` units a second. lines are in the
+    // arm's plane, so with the base turned x is the reach out from it
+    pub fn goto_line_with(&mut self, x: f32, y: f32, speed: f32) -> Result<(), DriverError> {
+        let points = Calc::get_line_points(&self.get_plane_position(), &Point { x, y, z: 0.0 }, self.segment_length);
+        let points: Vec<Point> = points.iter().map(|x| self.to_world(x)).collect();
+
+        return self.follow_path(&points, speed)
+    }
+
+    // a straight line in the world, turning the base along the way when z changes
+    pub fn goto_line_3d(&mut self, x: f32, y: f32, z: f32, speed: f32) -> Result<(), DriverError> {
+        let points = Calc::get_line_points(&self.current_position, &Point { x, y, z }, self.segment_length);
+
+        return self.follow_path(&points, speed)
+    }
+
+    // every joint goes straight for its target at the normal delay, the tool takes whatever path falls out
+    pub fn goto_joint(&mut self, x: f32, y: f32, z: f32) -> Result<(), DriverError> {
+        self.check_homed()?;
+
+        let mut segments = self.plan_path(&[Point { x, y, z }], 1.0)?;
+
+        for segment in segments.iter_mut() {
+            segment.delay = self.micro_delay_default;
+        }
+
+        return self.run_path(segments)
+    }
+
+    // points are in the world. everything is planned before the first step so an unreachable point
+    // halfway along stops nothing midway
+    pub fn follow_path(&mut self, points: &[Point], speed: f32) -> Result<(), DriverError> {
+        self.check_homed()?;
+
+        let segments = self.plan_path(points, speed)?;
+
+        return self.run_path(segments)
+    }
+
+    pub fn run_path(&mut self, segments: Vec<PathSegment>) -> Result<(), DriverError> {
+        let total: i32 = segments.iter().map(|x| x.get_lead()).sum();
+        let (_, step_degree) = self.get_lead(&[(Joint::Column, segments.iter().map(|x| x.column_steps.abs()).sum()),
+                                               (Joint::Beam, segments.iter().map(|x| x.beam_steps.abs()).sum()),
+                                               (Joint::Base, segments.iter().map(|x| x.base_steps.abs()).sum())]);
+        self.begin_move(total as u64, step_degree)?;
+
+        let motors = vec![Arc::clone(&self.column_motor), Arc::clone(&self.beam_motor), Arc::clone(&self.base_motor)];
+        let moves = segments.iter().map(|segment| {
+            let steps = vec![(segment.column_steps, segment.column_steps >= 0), (segment.beam_steps, segment.beam_steps >= 0),
+                             (segment.base_steps, segment.base_steps >= 0)];
+
+            (steps, vec![segment.delay; segment.get_lead() as usize])
+        }).collect();
+
+        let taken = self.join_step(Driver::stream_coordinated(motors, moves, Arc::clone(&self.control)))?;
+
+        for (i, joint) in Joint::all().iter().enumerate() {
+            self.count_steps(*joint, taken[i], taken[i] >= 0);
+        }
+
+        return self.end_move()
+    }
+
+    // ik on every point, each time taking the base direction and elbow solution closest to the point before it
+    pub fn plan_path(&self, points: &[Point], speed: f32) -> Result<Vec<PathSegment>, DriverError> {
+        let mut segments: Vec<PathSegment> = Vec::new();
+        let mut column = self.column_step_count;
+        let mut beam = self.beam_step_count;
+        let mut base = self.base_step_count;
+        let mut last = self.current_position.clone();
+
+        for point in points {
+            // past 90 degrees the base would rather flip round and reach back, so it's held to the side it's on
+            let base_angle = Calc::to_radian(base as f32 * self.base_step_degree);
+            let (planar, theta) = Calc::to_plane_near(point.x, point.y, point.z, base_angle);
+
+            if !self.calc.is_reachable(planar.x, planar.y) { return Err(DriverError::UnReachable) }
+
+            let mut angles = self.choose_nearest(self.calc.get_angle_solutions(planar.x, planar.y), column, beam);
+            angles.base_angle = theta;
+            self.check_limits(&angles)?;
+
+            let column_degree = Calc::to_degree(angles.column_angle);
+            let column_target = self.get_target_steps(Joint::Column, column_degree);
+            let beam_target = self.get_target_steps(Joint::Beam, Calc::to_degree(angles.beam_angle) - column_degree);
+            let base_target = self.get_target_steps(Joint::Base, Calc::to_degree(angles.base_angle));
+
+            let segment = PathSegment { column_steps: (column_target - column) as i32, beam_steps: (beam_target - beam) as i32,
+                                        base_steps: (base_target - base) as i32, delay: 0 };
+            let lead = segment.get_lead();
+
+            // segments too short to need a step carry over into the next one
+            if lead == 0 { continue }
+
+            let seconds = Calc::dist_3d(&last, point) / speed;
+            let delay = i64::max((seconds * 1_000_000.0 / lead as f32) as i64, self.get_speed_limit_delay(&segment));
+
+            segments.push(PathSegment { delay, ..segment });
+
+            column = column_target;
+            beam = beam_target;
+            base = base_target;
+            last = point.clone();
+        }
+
+        return Ok(segments)
+    }
+
+    // the shortest delay that keeps every joint of a segment at or under max_velocity. near the middle a
+    // little tool travel turns the joints a long way, so these segments are slowed rather than stepped flat out
+    fn get_speed_limit_delay(&self, segment: &PathSegment) -> i64 {
+        let lead = segment.get_lead() as f32;
+        let joints = [(Joint::Column, segment.column_steps), (Joint::Beam, segment.beam_steps), (Joint::Base, segment.base_steps)];
+
+        let micros = joints.iter()
+            .map(|(joint, steps)| steps.abs() as f32 * self.get_step_degree(*joint) * 1_000_000.0 / (lead * self.max_velocity))
+            .fold(0.0, f32::max);
+
+        return i64::max(micros.ceil() as i64, self.micro_delay_limit)
+    }
+
+    // a point in the arm's plane to where it is in the world with the base where it is now
+    pub fn to_world(&self, point: &Point) -> Point {
+        return Calc::rotate_about_base(point, Calc::to_radian(self.get_base_angle()), &self.calc.origin)
+    }
+
+    fn choose_nearest(&self, solutions: IkSolutions, column: i64, beam: i64) -> AngleSet {
+        let (elbow_up, elbow_down) = (Driver::wrap_angles(&solutions.elbow_up), Driver::wrap_angles(&solutions.elbow_down));
+        let up = self.get_travel_from(column, beam, &elbow_up);
+        let down = self.get_travel_from(column, beam, &elbow_down);
+
+        return if up <= down { elbow_up } else { elbow_down }
+    }
+}
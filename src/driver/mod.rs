@@ -0,0 +1,701 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use std::thread::{self, JoinHandle};
+use crate::stepper::{Motor, Stepper, TestStepper, StepRecorder};
+use crate::calc::{Calc, IkSolutions};
+use crate::utils::{ Point, AngleSet, ArmPose };
+use crate::config::{Config, HomingConfig};
+use crate::switch::{Switch, LimitSwitch, TestSwitch};
+use crate::tool::{Tool, ToolOutput, TestTool};
+use crate::profile::{MotionProfile, ProfileKind, Polynomial, Trapezoidal, SCurve};
+
+mod homing;
+mod steps;
+mod linear;
+mod arc;
+mod angles;
+mod control;
+mod plan;
+
+pub use arc::ArcShape;
+pub use control::{MotionControl, Brake};
+pub use steps::JointSteps;
+pub use plan::{MovePlan, PlanTiming};
+
+#[cfg(test)]
+mod tests;
+
+pub type MotorHandle = Arc<Mutex<Box<dyn Motor>>>;
+
+// signed steps and direction for each motor, then the delay after every step of the one moving most
+pub type CoordinatedMove = (Vec<(i32, bool)>, Vec<i64>);
+
+pub struct Driver {
+    pub column_motor: MotorHandle,
+    pub beam_motor: MotorHandle,
+    pub base_motor: MotorHandle,
+    pub column_step_count: i64,
+    pub beam_step_count: i64,
+    pub base_step_count: i64,
+    pub column_step_degree: f32,
+    pub beam_step_degree: f32,
+    pub base_step_degree: f32,
+    pub movement_amount: f32,
+    pub micro_delay_default: i64,
+    pub micro_delay_max: i64,
+    pub micro_delay_min: i64,
+    pub micro_delay_limit: i64,
+    pub profile: ProfileKind,
+    pub max_velocity: f32,
+    pub acceleration: f32,
+    pub jerk: f32,
+    pub coordinated: bool,
+    pub tool_speed: f32,
+    pub segment_length: f32,
+    pub current_position: Point,
+    pub generic_step_amount: i32,
+    pub elbow_policy: ElbowPolicy,
+    pub column_limits: JointLimits,
+    pub beam_limits: JointLimits,
+    pub base_limits: JointLimits,
+    pub column_home: Option<HomeSwitch>,
+    pub beam_home: Option<HomeSwitch>,
+    pub base_home: Option<HomeSwitch>,
+    pub homing: HomingConfig,
+    pub homed: bool,
+    pub tool: Option<Box<dyn Tool>>,
+    pub control: Arc<MotionControl>,
+    pub calc: Calc
+}
+
+pub struct HomeSwitch {
+    pub switch: Box<dyn Switch>,
+    pub angle: f32,
+    pub positive: bool
+}
+
+#[derive(Debug)]
+pub enum DriverError {
+    UnReachable,
+    MotorInit(String),
+    OutOfLimits(Joint, f32, JointLimits),
+    NotHomed,
+    HomingFailed(Joint, String),
+    InvalidPath(String),
+    Cancelled,
+    Stopped,
+    StalePlan,
+    MotorPanicked,
+    NoTool
+}
+
+impl DriverError {
+    pub fn get_message(&self) -> String {
+        match self {
+            DriverError::UnReachable => String::from("unable to reach target position, out of range"),
+
+            DriverError::MotorInit(e) => format!("unable to initialize motor: {}", e),
+
+            DriverError::OutOfLimits(joint, angle, limits) => format!("refusing to move, {} angle {:.2} is outside its limits {} to {}",
+                                                                      joint.get_name(), angle, limits.min, limits.max),
+
+            DriverError::NotHomed => String::from("refusing to move, the arm has not been homed"),
+
+            DriverError::HomingFailed(joint, reason) => format!("homing the {} failed: {}", joint.get_name(), reason),
+
+            DriverError::InvalidPath(reason) => format!("unable to plan path: {}", reason),
+
+            DriverError::NoTool => String::from("no tool configured, set tool.pin in the config"),
+
+            DriverError::Cancelled => String::from("move cancelled"),
+
+            DriverError::Stopped => String::from("emergency stop, the position is unknown until the arm is homed"),
+
+            DriverError::StalePlan => String::from("the arm moved since this move was planned, plan it again"),
+
+            DriverError::MotorPanicked => String::from("a motor thread failed, the position is unknown until the arm is homed")
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Joint {
+    Column,
+    Beam,
+    Base
+}
+
+impl Joint {
+    pub fn all() -> [Joint; 3] {
+        return [Joint::Column, Joint::Beam, Joint::Base]
+    }
+
+    pub fn parse(name: &str) -> Option<Joint> {
+        match name.to_lowercase().as_str() {
+            "column" => Some(Joint::Column),
+            "beam" => Some(Joint::Beam),
+            "base" => Some(Joint::Base),
+            _ => None
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Joint::Column => "column",
+            Joint::Beam => "beam",
+            Joint::Base => "base"
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Backend {
+    Hardware,
+    Simulated
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ElbowPolicy {
+    Up,
+    Down,
+    Nearest,
+    AvoidLimits
+}
+
+impl ElbowPolicy {
+    pub fn parse(name: &str) -> Option<ElbowPolicy> {
+        match name {
+            "up" => Some(ElbowPolicy::Up),
+            "down" => Some(ElbowPolicy::Down),
+            "near" | "nearest" => Some(ElbowPolicy::Nearest),
+            "safe" | "limits" => Some(ElbowPolicy::AvoidLimits),
+            _ => None
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            ElbowPolicy::Up => "up",
+            ElbowPolicy::Down => "down",
+            ElbowPolicy::Nearest => "near",
+            ElbowPolicy::AvoidLimits => "safe"
+        }
+    }
+}
+
+// degrees, the beam is limited relative to the column since that's what its joint sees
+#[derive(Clone, Copy, Debug)]
+pub struct JointLimits {
+    pub min: f32,
+    pub max: f32
+}
+
+impl JointLimits {
+    pub fn contains(&self, angle: f32) -> bool {
+        return angle >= self.min && angle <= self.max
+    }
+
+    pub fn margin(&self, angle: f32) -> f32 {
+        return f32::min(angle - self.min, self.max - angle)
+    }
+}
+
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right
+} 
+
+impl Driver {
+    pub fn new(backend: Backend, config: &Config) -> Result<Driver, DriverError> {
+        let mut motors: Vec<Box<dyn Motor>> = Vec::new();
+
+        for (name, axis) in config.get_axes() {
+            let (direction_pin, step_pin) = (axis.direction_pin, axis.step_pin);
+
+            let motor: Box<dyn Motor> = match backend {
+                Backend::Hardware => match Stepper::new(direction_pin, step_pin, axis.invert) {
+                    Ok(x) => Box::new(x),
+                    Err(e) => return Err(DriverError::MotorInit(format!("{} pins {}/{}: {}", name, direction_pin, step_pin, e)))
+                },
+
+                Backend::Simulated => Box::new(TestStepper::new(direction_pin, step_pin).inverted(axis.invert))
+            };
+
+            motors.push(motor);
+        }
+
+        let base_motor = motors.pop().unwrap();
+        let beam_motor = motors.pop().unwrap();
+        let column_motor = motors.pop().unwrap();
+
+        let mut driver = Driver::with_motors(column_motor, beam_motor, base_motor, config);
+
+        for (joint, (name, axis)) in Joint::all().into_iter().zip(config.get_axes()) {
+            let pin = match axis.home_pin {
+                Some(x) => x,
+                None => continue
+            };
+
+            let switch: Box<dyn Switch> = match backend {
+                Backend::Hardware => match LimitSwitch::new(pin, config.homing.active_low) {
+                    Ok(x) => Box::new(x),
+                    Err(e) => return Err(DriverError::MotorInit(format!("{} home pin {}: {}", name, pin, e)))
+                },
+
+                Backend::Simulated => {
+                    // the simulated joint starts at 0 degrees, put the switch where home_angle is from there.
+                    // positive joint steps drive the direction pin low unless the axis is inverted
+                    let trace = driver.get_motor(joint).lock().unwrap().get_recorder().unwrap();
+                    let sign = if axis.invert { 1 } else { -1 };
+                    let home_steps = (axis.home_angle / driver.get_step_degree(joint)).round() as i64;
+
+                    Box::new(TestSwitch::new(trace, home_steps * sign, axis.home_positive == (sign > 0)))
+                }
+            };
+
+            driver.set_home_switch(joint, HomeSwitch { switch, angle: axis.home_angle, positive: axis.home_positive });
+        }
+
+        if let Some(pin) = config.tool.pin {
+            let tool: Box<dyn Tool> = match backend {
+                Backend::Hardware => match ToolOutput::new(pin, config.tool.active_low) {
+                    Ok(x) => Box::new(x),
+                    Err(e) => return Err(DriverError::MotorInit(format!("tool pin {}: {}", pin, e)))
+                },
+
+                Backend::Simulated => Box::new(TestTool::new())
+            };
+
+            driver.tool = Some(tool);
+        }
+
+        Ok(driver)
+    }
+
+    pub fn with_motors(column_motor: Box<dyn Motor>, beam_motor: Box<dyn Motor>, base_motor: Box<dyn Motor>,
+                       config: &Config) -> Driver {
+        let column_motor = Arc::new(Mutex::new(column_motor));
+        let beam_motor = Arc::new(Mutex::new(beam_motor));
+        let base_motor = Arc::new(Mutex::new(base_motor));
+        let column_step_degree = config.column.get_step_degree();
+        let beam_step_degree = config.beam.get_step_degree();
+        let base_step_degree = config.base.get_step_degree();
+        let movement_amount = config.ui.movement_amount;
+        let micro_delay_default = config.motion.delay;
+        let micro_delay_min = config.motion.smooth_min_delay;
+        let micro_delay_max = config.motion.smooth_max_delay;
+        let micro_delay_limit = config.motion.delay_limit;
+        let profile = config.get_profile();
+        let max_velocity = config.motion.max_velocity;
+        let acceleration = config.motion.acceleration;
+        let jerk = config.motion.jerk;
+        let coordinated = config.motion.coordinated;
+        let tool_speed = config.motion.tool_speed;
+        let segment_length = config.motion.segment_length;
+        let generic_step_amount = config.ui.step_amount;
+        let elbow_policy = config.get_elbow_policy();
+        let column_limits = config.column.get_limits();
+        let beam_limits = config.beam.get_limits();
+        let base_limits = config.base.get_limits();
+        let links = &config.links;
+        let calc = Calc::new(links.origin_x, links.origin_y, links.column_length, links.beam_length);
+        let current_position = Point { x: calc.origin.x + calc.max_reach(), y: calc.origin.y, z: 0.0 };
+        let homing = config.homing.clone();
+        let homed = true; // nothing to home until a switch is set
+
+        return Driver { column_motor, beam_motor, base_motor,
+                        column_step_count: 0, beam_step_count: 0, base_step_count: 0,
+                        column_step_degree, beam_step_degree, base_step_degree,
+                        movement_amount,micro_delay_default, micro_delay_max, micro_delay_min, micro_delay_limit,
+                        profile, max_velocity, acceleration, jerk, coordinated, tool_speed, segment_length,
+                        current_position,
+                        generic_step_amount, elbow_policy, column_limits, beam_limits, base_limits,
+                        column_home: None, beam_home: None, base_home: None, homing, homed, tool: None,
+                        control: Arc::new(MotionControl::default()), calc
+        }
+    }
+
+    pub fn goto_point(&mut self, x: f32, y: f32) -> Result<(), DriverError>{
+        return self.goto_point_with(x, y, self.elbow_policy)
+    }
+
+    pub fn goto_point_with(&mut self, x: f32, y: f32, policy: ElbowPolicy) -> Result<(), DriverError>{
+        self.check_homed()?;
+
+        let plan = self.plan_point(x, y, policy, None)?;
+
+        return self.run_plan(&plan)
+    }
+
+    pub fn goto_point_smooth_with(&mut self, x: f32, y: f32, policy: ElbowPolicy, profile: ProfileKind) -> Result<(), DriverError>{
+        self.check_homed()?;
+
+        let plan = self.plan_point(x, y, policy, Some(profile))?;
+
+        return self.run_plan(&plan)
+    }
+
+    // built fresh every move so delay and speed changes from the ui apply straight away
+    pub fn get_profile(&self, kind: ProfileKind) -> Box<dyn MotionProfile> {
+        let min_delay = self.micro_delay_limit;
+
+        match kind {
+            ProfileKind::Polynomial => Box::new(Polynomial { min_delay: self.micro_delay_min, max_delay: self.micro_delay_max }),
+
+            ProfileKind::Trapezoidal => Box::new(Trapezoidal { max_velocity: self.max_velocity, acceleration: self.acceleration,
+                                                               min_delay }),
+
+            ProfileKind::SCurve => Box::new(SCurve { max_velocity: self.max_velocity, acceleration: self.acceleration,
+                                                     jerk: self.jerk, min_delay })
+        }
+    }
+
+    // every joint goes straight for its target at the default delay
+    pub fn goto_point_3d_with(&mut self, x: f32, y: f32, z: f32, policy: ElbowPolicy) -> Result<(), DriverError> {
+        self.check_homed()?;
+
+        let plan = self.plan_point_3d(x, y, z, policy, None)?;
+
+        return self.run_plan(&plan)
+    }
+
+    // joint travel in degrees the motors would see going from the current angles to the solution
+    fn get_travel(&self, angles: &AngleSet) -> f32 {
+        return self.get_travel_from(self.column_step_count, self.beam_step_count, angles)
+    }
+
+    // same as get_travel but starting from step counts that haven't been reached yet
+    fn get_travel_from(&self, column_step_count: i64, beam_step_count: i64, angles: &AngleSet) -> f32 {
+        let column = Calc::to_degree(angles.column_angle);
+        let beam = Calc::to_degree(angles.beam_angle);
+
+        let column_travel = f32::abs(column - column_step_count as f32 * self.column_step_degree);
+        let beam_travel = f32::abs((beam - column) - beam_step_count as f32 * self.beam_step_degree);
+
+        return column_travel + beam_travel
+    }
+
+    fn get_limit_margin(&self, angles: &AngleSet) -> f32 {
+        let column = Calc::to_degree(angles.column_angle);
+        let beam = Calc::to_degree(angles.beam_angle);
+
+        return f32::min(self.column_limits.margin(column), self.beam_limits.margin(beam - column))
+    }
+
+    // the ik only cares which way the links point, so its column and relative beam angles can come out a turn
+    // off. each is brought into (-180, 180] so the joint goes the short way and isn't refused by its limits
+    pub fn wrap_angles(angles: &AngleSet) -> AngleSet {
+        let column = Calc::wrap_radian(angles.column_angle);
+        let relative = angles.beam_angle - column;
+
+        // the beam turned by however much its relative angle had to be, nothing when it was in range already
+        let beam = angles.beam_angle + (Calc::wrap_radian(relative) - relative);
+
+        return AngleSet { column_angle: column, beam_angle: beam, base_angle: angles.base_angle }
+    }
+
+    // angles in radians with an absolute beam angle, like the ik returns them
+    pub fn check_limits(&self, angles: &AngleSet) -> Result<(), DriverError> {
+        let column = Calc::to_degree(angles.column_angle);
+        let beam = Calc::to_degree(angles.beam_angle) - column;
+        let base = Calc::to_degree(angles.base_angle);
+
+        for (joint, angle, limits) in [(Joint::Column, column, self.column_limits), (Joint::Beam, beam, self.beam_limits),
+                                       (Joint::Base, base, self.base_limits)] {
+            if !limits.contains(angle) {
+                return Err(DriverError::OutOfLimits(joint, angle, limits))
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn choose_solution(&self, solutions: IkSolutions, policy: ElbowPolicy) -> AngleSet {
+        let (elbow_up, elbow_down) = (Driver::wrap_angles(&solutions.elbow_up), Driver::wrap_angles(&solutions.elbow_down));
+
+        let prefer_up = match policy {
+            ElbowPolicy::Up => true,
+            ElbowPolicy::Down => false,
+            ElbowPolicy::Nearest => self.get_travel(&elbow_up) <= self.get_travel(&elbow_down),
+            ElbowPolicy::AvoidLimits => self.get_limit_margin(&elbow_up) >= self.get_limit_margin(&elbow_down)
+        };
+
+        return if prefer_up { elbow_up } else { elbow_down }
+    }
+
+    pub fn move_direction(&mut self, direction: Direction) -> Result<(), DriverError> {
+        match direction {
+            Direction::Left => {
+                return self.goto_point(self.current_position.x - self.movement_amount, self.current_position.y);                
+            },
+
+            Direction::Right => {
+                return self.goto_point(self.current_position.x + self.movement_amount, self.current_position.y);
+            },
+
+            Direction::Up => {
+                return self.goto_point(self.current_position.x, self.current_position.y + self.movement_amount);
+            },
+            
+            Direction::Down => {
+                return self.goto_point(self.current_position.x, self.current_position.y - self.movement_amount);
+            }
+        } 
+    }
+
+    // every stepping function returns the signed steps each motor actually took, which is less than asked
+    // when it was cancelled or stopped on the way
+    pub fn move_motor(motor: &mut MotorHandle, steps: i32, dir: bool, delay: i64, control: Arc<MotionControl>) -> JoinHandle<i32> {
+        return Driver::move_motor_smooth(motor, vec![delay; i32::unsigned_abs(steps) as usize], dir, control)
+    }
+
+    pub fn move_motor_smooth(motor: &mut MotorHandle, delays: Vec<i64>, dir: bool, control: Arc<MotionControl>) -> JoinHandle<i32> {
+        let motor = Arc::clone(motor);
+
+        thread::spawn(move || {
+            let mut motor = motor.lock().unwrap();
+            let mut brake = Brake::default();
+            let mut taken = 0;
+
+            for delay in delays {
+                let delay = match brake.next_delay(&control, delay) {
+                    Some(x) => x,
+                    None => break
+                };
+
+                motor.step(!dir);
+                thread::sleep(Duration::from_micros(delay as u64));
+                motor.reset();
+                thread::sleep(Duration::from_micros(10));
+
+                taken += 1;
+                control.tick();
+            }
+
+            if dir { taken } else { -taken }
+        })
+    }
+
+    // one thread steps every motor off the same clock. the motor with the most steps takes one every
+    // delay and the rest are spread over those ticks bresenham style, so they all start and finish together
+    pub fn move_motors_coordinated(moves: Vec<(MotorHandle, i32, bool)>, delays: Vec<i64>, control: Arc<MotionControl>) -> JoinHandle<Vec<i32>> {
+        thread::spawn(move || {
+            let mut motors: Vec<_> = moves.iter().map(|(motor, _, _)| motor.lock().unwrap()).collect();
+            let steps: Vec<(i32, bool)> = moves.iter().map(|(_, steps, dir)| (*steps, *dir)).collect();
+
+            Driver::step_coordinated(&mut motors, &steps, &delays, &control, &mut Brake::default())
+        })
+    }
+
+    // a path is many coordinated moves back to back, run without letting go of the motors in between.
+    // a cancel keeps braking across segments so the tool slows down along the path
+    pub fn stream_coordinated(motors: Vec<MotorHandle>, moves: Vec<CoordinatedMove>, control: Arc<MotionControl>) -> JoinHandle<Vec<i32>> {
+        thread::spawn(move || {
+            let mut guards: Vec<_> = motors.iter().map(|motor| motor.lock().unwrap()).collect();
+            let mut brake = Brake::default();
+            let mut taken = vec![0; motors.len()];
+
+            for (steps, delays) in moves {
+                let segment = Driver::step_coordinated(&mut guards, &steps, &delays, &control, &mut brake);
+
+                for (total, segment) in taken.iter_mut().zip(segment) {
+                    *total += segment;
+                }
+
+                if control.is_stopped() || (control.is_cancelled() && brake.is_stopped()) { break }
+            }
+
+            taken
+        })
+    }
+
+    fn step_coordinated(motors: &mut [MutexGuard<Box<dyn Motor>>], steps: &[(i32, bool)], delays: &[i64],
+                        control: &MotionControl, brake: &mut Brake) -> Vec<i32> {
+        let lead = steps.iter().map(|(steps, _)| i32::abs(*steps)).max().unwrap_or(0);
+        let mut errors: Vec<i32> = vec![lead / 2; steps.len()];
+        let mut taken: Vec<i32> = vec![0; steps.len()];
+
+        for delay in delays.iter().take(lead as usize) {
+            let delay = match brake.next_delay(control, *delay) {
+                Some(x) => x,
+                None => break
+            };
+
+            let mut stepped: Vec<usize> = Vec::new();
+
+            for (i, (steps, dir)) in steps.iter().enumerate() {
+                errors[i] -= i32::abs(*steps);
+
+                if errors[i] < 0 {
+                    errors[i] += lead;
+                    motors[i].step(!dir);
+                    stepped.push(i);
+                    taken[i] += if *dir { 1 } else { -1 };
+                }
+            }
+
+            thread::sleep(Duration::from_micros(delay as u64));
+
+            for i in stepped {
+                motors[i].reset();
+            }
+
+            thread::sleep(Duration::from_micros(10));
+            control.tick();
+        }
+
+        return taken
+    }
+
+    // a motor thread that panicked took however many steps it got to, so the counters can't be trusted
+    // until the arm is homed again
+    fn join_step<T>(&mut self, thread: JoinHandle<T>) -> Result<T, DriverError> {
+        match thread.join() {
+            Ok(x) => Ok(x),
+            Err(_) => {
+                self.homed = false;
+                Err(DriverError::MotorPanicked)
+            }
+        }
+    }
+
+    // the most steps any joint takes and that joint's step size, which is what braking is worked out from
+    fn get_lead(&self, steps: &[(Joint, i32)]) -> (i32, f32) {
+        let (joint, lead) = steps.iter().max_by_key(|(_, steps)| steps.abs()).map(|(joint, steps)| (*joint, steps.abs()))
+                                 .unwrap_or((Joint::Column, 0));
+
+        return (lead, self.get_step_degree(joint))
+    }
+
+    //FOR PUBLIC INTERFACE
+    // one joint on its own at the default delay, refused if it would end up past its limits
+    pub fn move_joint(&mut self, joint: Joint, steps: i32) -> Result<(), DriverError> {
+        self.check_homed()?;
+
+        let step_degree = self.get_step_degree(joint);
+        let angle = (self.get_step_count(joint) + steps as i64) as f32 * step_degree;
+        let limits = self.get_limits(joint);
+        if !limits.contains(angle) { return Err(DriverError::OutOfLimits(joint, angle, limits)) }
+
+        self.begin_move(steps.unsigned_abs() as u64, step_degree)?;
+
+        let mut motor = Arc::clone(self.get_motor(joint));
+        let thread = Driver::move_motor(&mut motor, steps, steps >= 0, self.micro_delay_default, Arc::clone(&self.control));
+        let taken = self.join_step(thread)?;
+        self.count_steps(joint, taken, taken >= 0);
+
+        return self.end_move()
+    }
+
+    // moves are whatever is left between the rounded target and the step counters, so going back
+    // to the same point always lands on the same steps. angles in radians, the beam absolute
+    pub fn get_steps_2d(&self, column_angle: f32, beam_angle: f32) -> JointSteps {
+        let column = Calc::to_degree(column_angle);
+
+        // the beam joint turns relative to the column, so that's the angle its gearing sees
+        let beam_relative = Calc::to_degree(beam_angle) - column;
+
+        let column_steps = self.get_target_steps(Joint::Column, column) - self.column_step_count;
+        let beam_steps = self.get_target_steps(Joint::Beam, beam_relative) - self.beam_step_count;
+
+        return JointSteps { column: column_steps as i32, beam: beam_steps as i32, base: 0 }
+    }
+
+    // the base lands on the step nearest its angle and is counted from there like the other two
+    pub fn get_steps_3d(&self, angles: &AngleSet) -> JointSteps {
+        let steps = self.get_steps_2d(angles.column_angle, angles.beam_angle);
+        let base_steps = self.get_target_steps(Joint::Base, Calc::to_degree(angles.base_angle)) - self.base_step_count;
+
+        return JointSteps { base: base_steps as i32, ..steps }
+    }
+
+    pub fn set_tool(&mut self, on: bool) -> Result<(), DriverError> {
+        match self.tool.as_mut() {
+            Some(tool) => tool.set(on),
+            None => return Err(DriverError::NoTool)
+        }
+
+        Ok(())
+    }
+
+    // for ending programs and stops, there's nothing to switch off without a tool
+    pub fn switch_tool_off(&mut self) {
+        if let Some(tool) = self.tool.as_mut() {
+            tool.set(false);
+        }
+    }
+
+    pub fn get_motor(&self, joint: Joint) -> &MotorHandle {
+        match joint {
+            Joint::Column => &self.column_motor,
+            Joint::Beam => &self.beam_motor,
+            Joint::Base => &self.base_motor
+        }
+    }
+
+    pub fn get_step_degree(&self, joint: Joint) -> f32 {
+        match joint {
+            Joint::Column => self.column_step_degree,
+            Joint::Beam => self.beam_step_degree,
+            Joint::Base => self.base_step_degree
+        }
+    }
+
+    pub fn get_limits(&self, joint: Joint) -> JointLimits {
+        match joint {
+            Joint::Column => self.column_limits,
+            Joint::Beam => self.beam_limits,
+            Joint::Base => self.base_limits
+        }
+    }
+
+    pub fn get_angle_set(&self) -> AngleSet {
+        return AngleSet {
+            column_angle: Calc::to_radian(self.get_column_angle()),
+            beam_angle: Calc::to_radian(self.get_beam_angle()),
+            base_angle: Calc::to_radian(self.get_base_angle())
+        }
+    }
+
+    pub fn get_pose(&self) -> ArmPose {
+        return self.calc.get_pose(&self.get_angle_set())
+    }
+
+    // the end effector in the arm's own plane, x is the reach out from the base whichever way it's turned
+    pub fn get_plane_position(&self) -> Point {
+        let mut angles = self.get_angle_set();
+        angles.base_angle = 0.0;
+
+        return self.calc.get_pose(&angles).end_effector
+    }
+
+    pub fn get_column_position(&self) -> Point {
+        return self.get_pose().elbow
+    }
+
+    pub fn get_beam_position(&self) -> Point {
+        return self.get_pose().end_effector
+    }
+
+    pub fn describe_motors(&self) -> String {
+        let column = self.column_motor.lock().unwrap().describe();
+        let beam = self.beam_motor.lock().unwrap().describe();
+        let base = self.base_motor.lock().unwrap().describe();
+
+        return format!("{}, {}, {}", column, beam, base)
+    }
+
+    pub fn get_recorders(&self) -> Vec<(&str, StepRecorder)> {
+        let motors = [("column", &self.column_motor), ("beam", &self.beam_motor), ("base", &self.base_motor)];
+
+        return motors.iter()
+            .filter_map(|(name, motor)| motor.lock().unwrap().get_recorder().map(|x| (*name, x)))
+            .collect()
+    }
+
+    pub fn get_current_position(&self) -> Point {
+        return self.get_pose().end_effector
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::calc::Calc;
+use crate::driver::{Driver, DriverError, ElbowPolicy, Joint, JointSteps};
+use crate::profile::ProfileKind;
+use crate::utils::AngleSet;
+
+// what the stepping loops sleep after every step on top of its delay
+const STEP_RESET_MICROS: u64 = 10;
+
+// how the steps of a plan are spread out over time
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlanTiming {
+    // one delay per step of the joint with the most steps, the others are dragged along on the same clock
+    Coordinated(Vec<i64>),
+    // every joint on its own thread at its own pace, column, beam then base
+    Independent([Vec<i64>; 3])
+}
+
+// a move worked out before anything turns, so it can be looked at, logged and tested on its own. it's made
+// against the step counters as they are and is only good until something else moves the arm
+#[derive(Clone, Debug, PartialEq)]
+pub struct MovePlan {
+    pub start: [i64; 3], // column, beam and base counters it was planned from
+    pub steps: JointSteps,
+    pub target: AngleSet, // where the joints land once rounded onto steps, radians with an absolute beam
+    pub timing: PlanTiming
+}
+
+impl MovePlan {
+    pub fn get_direction(&self, joint: Joint) -> bool {
+        return self.steps.get_direction(joint)
+    }
+
+    // the joint taking the most steps and how many
+    pub fn get_lead(&self) -> (Joint, i32) {
+        let lead = self.steps.get_lead();
+
+        return (lead, self.steps.get(lead).abs())
+    }
+
+    // how long the motors should take if nothing cancels them
+    pub fn get_duration(&self) -> Duration {
+        let total = |delays: &[i64]| -> u64 { delays.iter().map(|x| *x as u64 + STEP_RESET_MICROS).sum() };
+
+        let micros = match &self.timing {
+            PlanTiming::Coordinated(delays) => total(&delays[..usize::min(self.get_lead().1 as usize, delays.len())]),
+            PlanTiming::Independent(delays) => delays.iter().map(|x| total(x)).max().unwrap_or(0)
+        };
+
+        return Duration::from_micros(micros)
+    }
+
+    // one line for the output pane and logs, steps with the angle each joint ends on in degrees
+    pub fn describe(&self) -> String {
+        let column = Calc::to_degree(self.target.column_angle);
+        let angles = [column, Calc::to_degree(self.target.beam_angle) - column, Calc::to_degree(self.target.base_angle)];
+
+        let joints: Vec<String> = Joint::all().iter().zip(angles).map(|(joint, angle)| {
+            format!("{} {:+} to {:.2}deg", joint.get_name(), self.steps.get(*joint), angle)
+        }).collect();
+
+        let timing = match self.timing {
+            PlanTiming::Coordinated(_) => "coordinated",
+            PlanTiming::Independent(_) => "independent"
+        };
+
+        return format!("{}, {} over {:.2}s", joints.join(", "), timing, self.get_duration().as_secs_f32())
+    }
+}
+
+// the planner works everything out from the driver's state without touching a motor, run_plan is the only
+// part that steps them
+impl Driver {
+    // 2d targets leave the base where it is. without a profile every step waits the default delay
+    pub fn plan_point(&self, x: f32, y: f32, policy: ElbowPolicy, profile: Option<ProfileKind>) -> Result<MovePlan, DriverError> {
+        if !self.calc.is_reachable(x, y) { return Err(DriverError::UnReachable) }
+
+        let angles = self.choose_solution(self.calc.get_angle_solutions(x, y), policy);
+        self.check_limits(&angles)?;
+
+        let steps = self.get_steps_2d(angles.column_angle, angles.beam_angle);
+
+        return Ok(self.plan_steps(steps, self.get_timing(&steps, profile)))
+    }
+
+    // reach is from the base, wherever the arm happens to be now
+    pub fn plan_point_3d(&self, x: f32, y: f32, z: f32, policy: ElbowPolicy, profile: Option<ProfileKind>) -> Result<MovePlan, DriverError> {
+        if !self.calc.is_reachable_3d(x, y, z) { return Err(DriverError::UnReachable) }
+
+        let angles = self.choose_solution(self.calc.get_angle_solutions_3d(x, y, z), policy);
+        self.check_limits(&angles)?;
+
+        let steps = self.get_steps_3d(&angles);
+
+        return Ok(self.plan_steps(steps, self.get_timing(&steps, profile)))
+    }
+
+    // steps from the counters as they are now
+    pub fn plan_steps(&self, steps: JointSteps, timing: PlanTiming) -> MovePlan {
+        let start = Joint::all().map(|joint| self.get_step_count(joint));
+        let column = (start[0] + steps.column as i64) as f32 * self.column_step_degree;
+        let beam = (start[1] + steps.beam as i64) as f32 * self.beam_step_degree;
+        let base = (start[2] + steps.base as i64) as f32 * self.base_step_degree;
+
+        let target = AngleSet {
+            column_angle: Calc::to_radian(column),
+            beam_angle: Calc::to_radian(column + beam),
+            base_angle: Calc::to_radian(base)
+        };
+
+        return MovePlan { start, steps, target, timing }
+    }
+
+    // a coordinated profile runs on whichever joint has the most steps, independent ones each run their own
+    fn get_timing(&self, steps: &JointSteps, profile: Option<ProfileKind>) -> PlanTiming {
+        let delays = |joint: Joint| -> Vec<i64> {
+            let count = steps.get(joint).unsigned_abs();
+
+            match profile {
+                Some(x) => self.get_profile(x).get_delays(count, self.get_step_degree(joint)),
+                None => vec![self.micro_delay_default; count as usize]
+            }
+        };
+
+        if !self.coordinated { return PlanTiming::Independent(Joint::all().map(delays)) }
+
+        return PlanTiming::Coordinated(delays(steps.get_lead()))
+    }
+
+    pub fn run_plan(&mut self, plan: &MovePlan) -> Result<(), DriverError> {
+        self.check_homed()?;
+
+        if plan.start != Joint::all().map(|joint| self.get_step_count(joint)) { return Err(DriverError::StalePlan) }
+
+        let (lead, lead_steps) = plan.get_lead();
+        let motors = Joint::all().map(|joint| Arc::clone(self.get_motor(joint)));
+
+        let taken = match &plan.timing {
+            PlanTiming::Coordinated(delays) => {
+                self.begin_move(lead_steps as u64, self.get_step_degree(lead))?;
+
+                let moves = Joint::all().iter().zip(motors)
+                                 .map(|(joint, motor)| (motor, plan.steps.get(*joint), plan.get_direction(*joint))).collect();
+
+                self.join_step(Driver::move_motors_coordinated(moves, delays.clone(), Arc::clone(&self.control)))?
+            },
+
+            PlanTiming::Independent(delays) => {
+                self.begin_move(plan.steps.get_total() as u64, self.get_step_degree(lead))?;
+
+                let threads: Vec<JoinHandle<i32>> = Joint::all().iter().zip(motors).enumerate().map(|(i, (joint, mut motor))| {
+                    Driver::move_motor_smooth(&mut motor, delays[i].clone(), plan.get_direction(*joint), Arc::clone(&self.control))
+                }).collect();
+
+                // every thread is joined before a failed one is reported, so none is left stepping
+                let joined: Vec<Result<i32, DriverError>> = threads.into_iter().map(|x| self.join_step(x)).collect();
+                joined.into_iter().collect::<Result<Vec<i32>, DriverError>>()?
+            }
+        };
+
+        for (i, joint) in Joint::all().iter().enumerate() {
+            self.count_steps(*joint, taken[i], taken[i] >= 0);
+        }
+
+        return self.end_move()
+    }
+}
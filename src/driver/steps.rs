@@ -0,0 +1,92 @@
+use crate::driver::{Driver, Joint};
+
+// how far each joint has to go for a move, signed like count_steps takes them. the beam is relative to the column
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct JointSteps {
+    pub column: i32,
+    pub beam: i32,
+    pub base: i32
+}
+
+impl JointSteps {
+    pub fn get(&self, joint: Joint) -> i32 {
+        match joint {
+            Joint::Column => self.column,
+            Joint::Beam => self.beam,
+            Joint::Base => self.base
+        }
+    }
+
+    // what move_motor takes as dir, true for positive steps
+    pub fn get_direction(&self, joint: Joint) -> bool {
+        return self.get(joint) >= 0
+    }
+
+    // the joint with the most steps, the one a coordinated move is timed on
+    pub fn get_lead(&self) -> Joint {
+        return Joint::all().into_iter().max_by_key(|joint| self.get(*joint).abs()).unwrap_or(Joint::Column)
+    }
+
+    pub fn get_total(&self) -> i32 {
+        return self.column.abs() + self.beam.abs() + self.base.abs()
+    }
+}
+
+// every step a motor emits gets counted here. the counters are the source of truth,
+// angles are worked out of them on demand and current_position after each move
+impl Driver {
+    // steps as passed to move_motor, only the magnitude is emitted and dir picks the sign
+    pub fn count_steps(&mut self, joint: Joint, steps: i32, dir: bool) {
+        let steps = i32::abs(steps) as i64;
+        let steps = if dir { steps } else { -steps };
+
+        match joint {
+            Joint::Column => self.column_step_count += steps,
+            Joint::Beam => self.beam_step_count += steps,
+            Joint::Base => self.base_step_count += steps
+        }
+
+        self.sync_position();
+    }
+
+    // the beam counter is relative to the column, like its joint
+    pub fn get_step_count(&self, joint: Joint) -> i64 {
+        match joint {
+            Joint::Column => self.column_step_count,
+            Joint::Beam => self.beam_step_count,
+            Joint::Base => self.base_step_count
+        }
+    }
+
+    pub fn set_step_count(&mut self, joint: Joint, steps: i64) {
+        match joint {
+            Joint::Column => self.column_step_count = steps,
+            Joint::Beam => self.beam_step_count = steps,
+            Joint::Base => self.base_step_count = steps
+        }
+
+        self.sync_position();
+    }
+
+    // degrees, rounded to the nearest step the joint can actually reach
+    pub fn get_target_steps(&self, joint: Joint, angle: f32) -> i64 {
+        return (angle / self.get_step_degree(joint)).round() as i64
+    }
+
+    pub fn get_column_angle(&self) -> f32 {
+        return self.column_step_count as f32 * self.column_step_degree
+    }
+
+    // absolute, measured from the x axis like the ik's beam angle
+    pub fn get_beam_angle(&self) -> f32 {
+        return self.get_column_angle() + self.beam_step_count as f32 * self.beam_step_degree
+    }
+
+    pub fn get_base_angle(&self) -> f32 {
+        return self.base_step_count as f32 * self.base_step_degree
+    }
+
+    fn sync_position(&mut self) {
+        self.current_position = self.get_current_position();
+    }
+}
@@ -0,0 +1,760 @@
+use super::*;
+use std::time::Duration;
+use crate::stepper::{StepTrace, TRACE_CAPACITY};
+use crate::switch::TestSwitch;
+
+fn get_trace(driver: &Driver, joint: Joint) -> StepRecorder {
+    return driver.get_motor(joint).lock().unwrap().get_recorder().unwrap()
+}
+
+fn test_driver() -> (Driver, StepRecorder, StepRecorder) {
+    let driver = Driver::new(Backend::Simulated, &Config::default()).unwrap();
+    let (column_trace, beam_trace) = (get_trace(&driver, Joint::Column), get_trace(&driver, Joint::Beam));
+
+    return (driver, column_trace, beam_trace)
+}
+
+// degrees with an absolute beam angle, rounded onto the step counters
+fn set_angles(driver: &mut Driver, column: f32, beam: f32) {
+    let column_steps = driver.get_target_steps(Joint::Column, column);
+    let beam_steps = driver.get_target_steps(Joint::Beam, beam - column);
+
+    driver.set_step_count(Joint::Column, column_steps);
+    driver.set_step_count(Joint::Beam, beam_steps);
+}
+
+#[test]
+fn move_motor_emits_one_edge_per_step() {
+    let (mut driver, column_trace, beam_trace) = test_driver();
+    driver.micro_delay_default = 50;
+
+    driver.move_joint(Joint::Column, 25).unwrap();
+    driver.move_joint(Joint::Beam, -10).unwrap();
+
+    let column_trace = column_trace.lock().unwrap();
+    let beam_trace = beam_trace.lock().unwrap();
+
+    assert_eq!(column_trace.get_step_count(), 25);
+    assert_eq!(beam_trace.get_step_count(), 10);
+    // positive steps drive the direction pin low
+    assert_eq!(column_trace.get_position(), -25);
+    assert_eq!(beam_trace.get_position(), 10);
+    assert_eq!(column_trace.get_direction_changes(), vec![false]);
+    assert_eq!(beam_trace.get_direction_changes(), vec![true]);
+}
+
+#[test]
+fn step_traces_keep_only_the_newest_events() {
+    let mut trace = StepTrace::new();
+    trace.set_direction(true);
+
+    for _ in 0..TRACE_CAPACITY {
+        trace.step();
+        trace.reset();
+    }
+
+    // the direction change and the first half of the steps have been dropped
+    assert_eq!(trace.get_step_count(), TRACE_CAPACITY / 2);
+    assert!(trace.get_direction_changes().is_empty());
+    assert_eq!(trace.get_position(), TRACE_CAPACITY as i64);
+    assert_eq!(trace.to_csv().lines().count(), TRACE_CAPACITY + 1);
+}
+
+#[test]
+fn move_motor_smooth_waits_out_every_delay() {
+    let (mut driver, column_trace, _) = test_driver();
+    let delays = Calc::normalize_vec(100, 400, Calc::smooth((1..=20).collect())).unwrap();
+
+    Driver::move_motor_smooth(&mut driver.column_motor, delays.clone(), true, Arc::clone(&driver.control)).join().unwrap();
+
+    let trace = column_trace.lock().unwrap();
+    let intervals = trace.get_step_intervals();
+
+    assert_eq!(trace.get_step_count(), 20);
+    assert_eq!(intervals.len(), 19);
+
+    for (interval, delay) in intervals.iter().zip(delays.iter()) {
+        assert!(*interval >= Duration::from_micros(*delay as u64));
+    }
+}
+
+#[test]
+fn current_position_follows_solved_angles() {
+    let (mut driver, _, _) = test_driver();
+    // fine enough gearing that rounding onto steps stays under the tolerance
+    driver.column_step_degree = 0.001;
+    driver.beam_step_degree = 0.001;
+
+    for (x, y) in [(1.0, 1.0), (-1.2, 0.4), (0.0, 1.5), (1.99, 0.0), (0.3, -0.8)] {
+        let angles = driver.calc.get_angles(x, y);
+        set_angles(&mut driver, Calc::to_degree(angles.column_angle), Calc::to_degree(angles.beam_angle));
+
+        let position = driver.get_current_position();
+
+        assert!(Calc::dist(position.x, position.y, x, y) < 1e-3, "{} {} read back as {} {}", x, y, position.x, position.y);
+    }
+}
+
+#[test]
+fn goto_point_refuses_targets_outside_annulus() {
+    let (mut driver, column_trace, _) = test_driver();
+    driver.calc = Calc::new(0.0, 0.0, 1.0, 0.5);
+
+    assert!(matches!(driver.goto_point(0.2, 0.2), Err(DriverError::UnReachable)));
+    assert!(matches!(driver.goto_point(1.2, 1.2), Err(DriverError::UnReachable)));
+    assert_eq!(column_trace.lock().unwrap().get_step_count(), 0);
+}
+
+#[test]
+fn nearest_policy_picks_least_joint_travel() {
+    let (mut driver, _, _) = test_driver();
+    driver.micro_delay_default = 10;
+
+    let solutions = driver.calc.get_angle_solutions(1.0, 0.5);
+    let down = solutions.elbow_down.clone();
+
+    set_angles(&mut driver, Calc::to_degree(down.column_angle) + 5.0, Calc::to_degree(down.beam_angle) + 5.0);
+
+    let chosen = driver.choose_solution(solutions.clone(), ElbowPolicy::Nearest);
+    assert_eq!(chosen.column_angle, down.column_angle);
+
+    let chosen = driver.choose_solution(solutions, ElbowPolicy::Up);
+    assert!(chosen.column_angle > down.column_angle);
+}
+
+#[test]
+fn avoid_limits_policy_keeps_joints_in_range() {
+    let (mut driver, _, _) = test_driver();
+    driver.column_limits = JointLimits { min: -60.0, max: 40.0 };
+
+    let solutions = driver.calc.get_angle_solutions(1.2, 0.3);
+    let chosen = driver.choose_solution(solutions.clone(), ElbowPolicy::AvoidLimits);
+
+    // elbow up needs the column past 60 degrees here
+    assert_eq!(chosen.column_angle, solutions.elbow_down.column_angle);
+    assert!(driver.column_limits.margin(Calc::to_degree(chosen.column_angle)) > 0.0);
+}
+
+#[test]
+fn limits_refuse_moves_before_stepping() {
+    let (mut driver, column_trace, beam_trace) = test_driver();
+    driver.micro_delay_default = 10;
+    driver.column_limits = JointLimits { min: 0.0, max: 60.0 };
+    driver.beam_limits = JointLimits { min: -170.0, max: 10.0 };
+
+    // elbow up for this target needs the column at about 90 degrees
+    match driver.goto_point_with(0.0, 1.2, ElbowPolicy::Up) {
+        Err(DriverError::OutOfLimits(Joint::Column, angle, _)) => assert!(angle > 60.0),
+        _ => panic!("move should be outside the column limits")
+    }
+
+    assert!(matches!(driver.move_joint(Joint::Column, -5), Err(DriverError::OutOfLimits(Joint::Column, _, _))));
+    assert!(matches!(driver.move_joint(Joint::Beam, 200), Err(DriverError::OutOfLimits(Joint::Beam, _, _))));
+    assert!(driver.move_joint(Joint::Beam, 100).is_ok());
+
+    assert_eq!(column_trace.lock().unwrap().get_step_count(), 0);
+    assert_eq!(beam_trace.lock().unwrap().get_step_count(), 100);
+}
+
+#[test]
+fn steps_follow_each_axis_gearing() {
+    let (mut driver, _, _) = test_driver();
+    driver.column_step_degree = 0.1;
+    driver.beam_step_degree = 0.5;
+    driver.base_step_degree = 0.25;
+
+    let steps = driver.get_steps_2d(Calc::to_radian(30.0), Calc::to_radian(50.0));
+
+    assert_eq!(steps.column, 300);
+    assert_eq!(steps.beam, 40);
+
+    let steps = driver.get_steps_3d(&AngleSet { column_angle: 0.0, beam_angle: 0.0, base_angle: Calc::to_radian(-45.0) });
+
+    assert_eq!(steps.base, -180);
+}
+
+#[test]
+fn goto_point_emits_planned_steps() {
+    let (mut driver, column_trace, beam_trace) = test_driver();
+    driver.micro_delay_default = 10;
+
+    let angles = driver.calc.get_angles(1.0, 1.0);
+    let steps = driver.get_steps_2d(angles.column_angle, angles.beam_angle);
+
+    driver.goto_point(1.0, 1.0).unwrap();
+
+    assert_eq!(column_trace.lock().unwrap().get_position(), -steps.column as i64);
+    assert_eq!(beam_trace.lock().unwrap().get_position(), -steps.beam as i64);
+}
+
+#[test]
+fn uncoordinated_goto_emits_the_same_steps() {
+    let (mut driver, column_trace, beam_trace) = test_driver();
+    driver.micro_delay_default = 10;
+    driver.micro_delay_min = 10;
+    driver.micro_delay_max = 40;
+    driver.coordinated = false;
+
+    driver.goto_point(1.0, 1.0).unwrap();
+    driver.goto_point_smooth_with(0.4, 1.5, driver.elbow_policy, driver.profile).unwrap();
+
+    assert_eq!(column_trace.lock().unwrap().get_position(), -driver.get_step_count(Joint::Column));
+    assert_eq!(beam_trace.lock().unwrap().get_position(), -driver.get_step_count(Joint::Beam));
+}
+
+// logs which motor stepped, in the order the steps happened
+struct OrderMotor {
+    id: usize,
+    log: Arc<Mutex<Vec<usize>>>
+}
+
+impl Motor for OrderMotor {
+    fn set_direction(&mut self, _direction: bool) {}
+
+    fn step(&mut self, _direction: bool) {
+        self.log.lock().unwrap().push(self.id);
+    }
+
+    fn reset(&mut self) {}
+
+    fn get_direction_pin(&self) -> u8 {
+        return 0
+    }
+
+    fn get_step_pin(&self) -> u8 {
+        return 0
+    }
+
+    fn is_simulated(&self) -> bool {
+        return true
+    }
+}
+
+#[test]
+fn coordinated_moves_interleave_steps() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let motor = |id| -> MotorHandle { Arc::new(Mutex::new(Box::new(OrderMotor { id, log: Arc::clone(&log) }))) };
+
+    let moves = vec![(motor(0), 90, true), (motor(1), -30, false), (motor(2), 0, true)];
+    Driver::move_motors_coordinated(moves, vec![10; 90], Arc::new(MotionControl::default())).join().unwrap();
+
+    let log = log.lock().unwrap();
+    let mut lead = 0;
+    let mut follower = 0;
+
+    for id in log.iter() {
+        if *id == 0 { lead += 1 } else { follower += 1 }
+
+        // one follower step every three lead steps, all the way through
+        assert!(i32::abs(lead - 3 * follower) <= 3, "{} lead steps against {} follower steps", lead, follower);
+    }
+
+    assert_eq!((lead, follower), (90, 30));
+    assert!(!log.contains(&2));
+}
+
+#[test]
+fn raw_moves_keep_angles_in_sync() {
+    let (mut driver, _, _) = test_driver();
+    driver.micro_delay_default = 10;
+
+    driver.move_joint(Joint::Column, 90).unwrap();
+    driver.move_joint(Joint::Beam, -45).unwrap();
+
+    let column = 90.0 * driver.column_step_degree;
+    let beam = column - 45.0 * driver.beam_step_degree;
+
+    assert!((driver.get_column_angle() - column).abs() < 1e-3);
+    assert!((driver.get_beam_angle() - beam).abs() < 1e-3);
+
+    let expected = driver.calc.get_pose(&driver.get_angle_set()).end_effector;
+    assert!(Calc::dist(driver.current_position.x, driver.current_position.y, expected.x, expected.y) < 1e-5);
+    assert!(driver.current_position.y > 0.0);
+}
+
+#[test]
+fn step_counts_match_what_the_motors_emitted() {
+    let (mut driver, column_trace, beam_trace) = test_driver();
+    driver.micro_delay_default = 10;
+    driver.micro_delay_min = 10;
+    driver.micro_delay_max = 40;
+
+    driver.goto_point(1.0, 1.0).unwrap();
+    driver.move_joint(Joint::Column, -30).unwrap();
+    driver.move_joint(Joint::Beam, 70).unwrap();
+    driver.goto_point_smooth_with(0.5, 1.2, driver.elbow_policy, driver.profile).unwrap();
+
+    // positive joint steps show up as negative pin positions on a non inverted motor
+    assert_eq!(driver.get_step_count(Joint::Column), -column_trace.lock().unwrap().get_position());
+    assert_eq!(driver.get_step_count(Joint::Beam), -beam_trace.lock().unwrap().get_position());
+}
+
+// switch closes once the column has turned `angle` degrees negative from where it started
+fn home_column_at(driver: &mut Driver, trace: &StepRecorder, angle: f32) {
+    let steps = (angle / driver.column_step_degree).round() as i64;
+    let switch = TestSwitch::new(Arc::clone(trace), -steps, true);
+
+    driver.homing.delay = 10;
+    driver.set_home_switch(Joint::Column, HomeSwitch { switch: Box::new(switch), angle: 5.0, positive: false });
+}
+
+#[test]
+fn motion_waits_for_homing() {
+    let (mut driver, column_trace, _) = test_driver();
+    driver.micro_delay_default = 10;
+    home_column_at(&mut driver, &column_trace, -10.0);
+
+    assert!(matches!(driver.goto_point(1.0, 1.0), Err(DriverError::NotHomed)));
+    assert!(matches!(driver.move_joint(Joint::Beam, 10), Err(DriverError::NotHomed)));
+
+    driver.home().unwrap();
+
+    // the switch sits 10 degrees down, backing off leaves the joint backoff_steps above home_angle
+    let backoff = driver.homing.backoff_steps as f32 * driver.column_step_degree;
+    assert!((driver.get_column_angle() - (5.0 + backoff)).abs() <= driver.column_step_degree / 2.0);
+    assert_eq!(column_trace.lock().unwrap().get_position(), (10.0 / driver.column_step_degree).round() as i64
+               - driver.homing.backoff_steps as i64);
+    assert!(driver.goto_point(1.0, 1.0).is_ok());
+}
+
+#[test]
+fn homing_starting_on_the_switch_clears_it_first() {
+    let (mut driver, column_trace, _) = test_driver();
+    home_column_at(&mut driver, &column_trace, 0.0);
+
+    driver.home().unwrap();
+
+    let backoff = driver.homing.backoff_steps as f32 * driver.column_step_degree;
+    assert!((driver.get_column_angle() - (5.0 + backoff)).abs() <= driver.column_step_degree / 2.0);
+    assert_eq!(column_trace.lock().unwrap().get_direction_changes(), vec![false, true, false]);
+}
+
+#[test]
+fn homing_gives_up_without_a_switch() {
+    let (mut driver, column_trace, _) = test_driver();
+    home_column_at(&mut driver, &column_trace, -90.0);
+    driver.homing.search_degrees = 20.0;
+
+    assert!(matches!(driver.home(), Err(DriverError::HomingFailed(Joint::Column, _))));
+    assert!(!driver.homed);
+}
+
+#[test]
+fn repeated_gotos_never_drift() {
+    let (mut driver, column_trace, beam_trace) = test_driver();
+    driver.micro_delay_default = 10;
+
+    driver.goto_point(1.0, 1.0).unwrap();
+    let column = driver.get_step_count(Joint::Column);
+    let beam = driver.get_step_count(Joint::Beam);
+
+    for _ in 0..5 {
+        driver.goto_point(0.7, 1.3).unwrap();
+        driver.goto_point(1.0, 1.0).unwrap();
+
+        assert_eq!(driver.get_step_count(Joint::Column), column);
+        assert_eq!(driver.get_step_count(Joint::Beam), beam);
+    }
+
+    // already there, nothing left to step
+    let emitted = column_trace.lock().unwrap().get_step_count() + beam_trace.lock().unwrap().get_step_count();
+    driver.goto_point(1.0, 1.0).unwrap();
+
+    assert_eq!(column_trace.lock().unwrap().get_step_count() + beam_trace.lock().unwrap().get_step_count(), emitted);
+    assert_eq!(column_trace.lock().unwrap().get_position(), -column);
+}
+
+// distance from p to the segment from a to b
+fn distance_to_segment(p: &Point, a: &Point, b: &Point) -> f32 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let t = f32::clamp(((p.x - a.x) * dx + (p.y - a.y) * dy) / (dx * dx + dy * dy), 0.0, 1.0);
+
+    return Calc::dist(p.x, p.y, a.x + dx * t, a.y + dy * t)
+}
+
+#[test]
+fn line_moves_stay_on_the_line() {
+    let (mut driver, _, _) = test_driver();
+    driver.micro_delay_default = 10;
+    driver.goto_point(1.5, 0.5).unwrap();
+
+    let start = driver.current_position.clone();
+    let end = Point { x: 0.5, y: 1.5, z: 0.0 };
+    let points = Calc::get_line_points(&start, &end, driver.segment_length);
+    let segments = driver.plan_path(&points, 0.2).unwrap();
+
+    let (mut column, mut beam) = (driver.column_step_count, driver.beam_step_count);
+    let mut seconds = 0.0;
+
+    for segment in &segments {
+        column += segment.column_steps as i64;
+        beam += segment.beam_steps as i64;
+        seconds += (segment.delay * i32::max(segment.column_steps.abs(), segment.beam_steps.abs()) as i64) as f32 / 1e6;
+
+        let column_angle = column as f32 * driver.column_step_degree;
+        let beam_angle = column_angle + beam as f32 * driver.beam_step_degree;
+        let angles = AngleSet { column_angle: Calc::to_radian(column_angle), beam_angle: Calc::to_radian(beam_angle), base_angle: 0.0 };
+        let position = driver.calc.get_pose(&angles).end_effector;
+
+        // a few steps of slack on top of the segment length
+        assert!(distance_to_segment(&position, &start, &end) < 0.01, "{} {} is off the line", position.x, position.y);
+    }
+
+    // sqrt(2) units at 0.2 a second
+    assert!((seconds - f32::sqrt(2.0) / 0.2).abs() < 0.1, "took {}", seconds);
+}
+
+#[test]
+fn line_moves_refuse_paths_through_the_middle() {
+    let (mut driver, column_trace, _) = test_driver();
+    driver.calc = Calc::new(0.0, 0.0, 1.0, 0.5);
+    driver.micro_delay_default = 10;
+    driver.goto_point(1.0, 0.5).unwrap();
+
+    let emitted = column_trace.lock().unwrap().get_step_count();
+
+    // both ends are in reach, the middle of the line is inside the inner radius
+    assert!(matches!(driver.goto_line(-1.0, 0.5), Err(DriverError::UnReachable)));
+    assert_eq!(column_trace.lock().unwrap().get_step_count(), emitted);
+}
+
+#[test]
+fn line_moves_count_every_step() {
+    let (mut driver, column_trace, beam_trace) = test_driver();
+    driver.micro_delay_default = 10;
+    driver.max_velocity = 100_000.0;
+    driver.goto_point(1.5, 0.5).unwrap();
+
+    driver.goto_line_with(1.3, 0.9, 50.0).unwrap();
+
+    assert!(Calc::dist(driver.current_position.x, driver.current_position.y, 1.3, 0.9) < 5e-3);
+    assert_eq!(column_trace.lock().unwrap().get_position(), -driver.get_step_count(Joint::Column));
+    assert_eq!(beam_trace.lock().unwrap().get_position(), -driver.get_step_count(Joint::Beam));
+}
+
+#[test]
+fn line_moves_keep_a_base_past_ninety_degrees_where_it_is() {
+    let (mut driver, _, _) = test_driver();
+    driver.micro_delay_default = 10;
+    driver.max_velocity = 100_000.0;
+    driver.goto_point(1.5, 0.5).unwrap();
+
+    let base = driver.get_target_steps(Joint::Base, 120.0);
+    driver.set_step_count(Joint::Base, base);
+
+    // re-solved on their own these points put the base near -60 with the arm reaching back over the top
+    let points: Vec<Point> = Calc::get_line_points(&driver.get_plane_position(), &Point { x: 1.3, y: 0.9, z: 0.0 }, driver.segment_length)
+        .iter().map(|x| driver.to_world(x)).collect();
+    let segments = driver.plan_path(&points, 0.2).unwrap();
+
+    assert!(segments.iter().all(|x| x.base_steps.abs() <= 1));
+    assert!(segments.iter().map(|x| x.column_steps.abs()).sum::<i32>() < driver.get_target_steps(Joint::Column, 30.0) as i32);
+
+    driver.goto_line_with(1.3, 0.9, 50.0).unwrap();
+
+    let plane = driver.get_plane_position();
+    assert!((driver.get_step_count(Joint::Base) - base).abs() <= 1);
+    assert!(Calc::dist(plane.x, plane.y, 1.3, 0.9) < 5e-3, "ended at {} {}", plane.x, plane.y);
+
+    // out at 170 degrees is a short turn past the limit, so it's refused rather than flipped round the long way
+    driver.base_limits = JointLimits { min: -180.0, max: 150.0 };
+    driver.set_step_count(Joint::Base, driver.get_target_steps(Joint::Base, 170.0));
+    let past = driver.to_world(&Point { x: 1.2, y: 0.5, z: 0.0 });
+
+    driver.set_step_count(Joint::Base, driver.get_target_steps(Joint::Base, 140.0));
+    assert!(matches!(driver.plan_path(&[past], 0.2), Err(DriverError::OutOfLimits(Joint::Base, _, _))));
+}
+
+#[test]
+fn path_segments_hold_every_joint_under_max_velocity() {
+    let (mut driver, _, _) = test_driver();
+    driver.micro_delay_default = 10;
+    driver.max_velocity = 30.0;
+    driver.goto_point(1.5, 0.5).unwrap();
+
+    // far faster than the joints are allowed to turn
+    let points = Calc::get_line_points(&driver.current_position, &Point { x: 0.5, y: 1.5, z: 0.0 }, driver.segment_length);
+    let segments = driver.plan_path(&points, 1000.0).unwrap();
+
+    for segment in &segments {
+        let seconds = (segment.delay * segment.get_lead() as i64) as f32 / 1e6;
+
+        for (joint, steps) in [(Joint::Column, segment.column_steps), (Joint::Beam, segment.beam_steps)] {
+            let velocity = steps.abs() as f32 * driver.get_step_degree(joint) / seconds;
+            assert!(velocity <= 30.0 + 1e-3, "{:?} at {}deg/s", joint, velocity);
+        }
+    }
+}
+
+#[test]
+fn arc_moves_follow_the_circle() {
+    let (mut driver, column_trace, _) = test_driver();
+    driver.micro_delay_default = 10;
+    driver.max_velocity = 100_000.0;
+    driver.goto_point(1.5, 0.5).unwrap();
+
+    let points = driver.get_arc_points(&driver.get_plane_position(), 1.0, 1.0, ArcShape::Through(1.0 + 0.5 * f32::cos(0.8), 0.5 + 0.5 * f32::sin(0.8)), true).unwrap();
+    assert!(points.iter().all(|x| (Calc::dist(x.x, x.y, 1.0, 0.5) - 0.5).abs() < 1e-3));
+
+    driver.goto_arc_with(1.0, 1.0, ArcShape::Center(1.0, 0.5), false, 50.0).unwrap();
+
+    assert!(Calc::dist(driver.current_position.x, driver.current_position.y, 1.0, 1.0) < 5e-3);
+    assert_eq!(column_trace.lock().unwrap().get_position(), -driver.get_step_count(Joint::Column));
+}
+
+#[test]
+fn arcs_that_cant_be_drawn_are_refused() {
+    let (mut driver, _, _) = test_driver();
+    driver.micro_delay_default = 10;
+    driver.goto_point(1.5, 0.5).unwrap();
+
+    assert!(matches!(driver.goto_arc(1.0, 1.0, ArcShape::Radius(0.1), false), Err(DriverError::InvalidPath(_))));
+    let start = driver.get_plane_position();
+    let via = ArcShape::Through((start.x + 0.5) / 2.0, (start.y + 0.5) / 2.0);
+    assert!(matches!(driver.goto_arc(0.5, 0.5, via, false), Err(DriverError::InvalidPath(_))));
+
+    // 0.5 from the center at the start and 0.6 at the end
+    assert!(matches!(driver.goto_arc(1.0, 1.1, ArcShape::Center(1.0, 0.5), false), Err(DriverError::InvalidPath(_))));
+
+    // the far side of this circle is out of reach
+    assert!(matches!(driver.goto_arc(1.5, 0.5, ArcShape::Center(2.0, 0.5), false), Err(DriverError::UnReachable)));
+}
+
+// cancels or stops from another thread once the move is well under way
+fn interrupt_after(control: &Arc<MotionControl>, millis: u64, stop: bool) -> JoinHandle<()> {
+    let control = Arc::clone(control);
+
+    return thread::spawn(move || {
+        thread::sleep(Duration::from_millis(millis));
+        if stop { control.emergency_stop() } else { control.cancel() }
+    })
+}
+
+#[test]
+fn brakes_slow_at_the_deceleration_then_stop() {
+    let control = MotionControl::default();
+    let mut brake = Brake::default();
+
+    control.begin(800, 10_000.0);
+    assert_eq!(brake.next_delay(&control, 1000), Some(1000));
+
+    // from 1000 steps a second, v^2 = 1000^2 - 2 * 10000 * n reaches zero on the 50th step. the first delay
+    // is 1e6 / sqrt(980000) and the last 1e6 / sqrt(20000)
+    control.cancel();
+    let delays: Vec<i64> = std::iter::from_fn(|| brake.next_delay(&control, 1000)).collect();
+
+    assert_eq!(delays.len(), 49);
+    assert_eq!((delays[0], delays[48]), (1010, 7071));
+    assert!(delays.windows(2).all(|x| x[1] > x[0]));
+    assert!(brake.is_stopped());
+
+    // a stop doesn't brake at all
+    control.emergency_stop();
+    assert_eq!(Brake::default().next_delay(&control, 1000), None);
+}
+
+#[test]
+fn cancel_brakes_to_a_stop() {
+    let (mut driver, column_trace, _) = test_driver();
+    driver.micro_delay_default = 1000;
+    // the 10000 steps/s^2 above, 49 steps to brake from 1000 steps a second
+    driver.acceleration = 10_000.0 * driver.column_step_degree;
+
+    // cancelled by steps rather than time, so however slowly the loop runs it brakes from the same place
+    let control = Arc::clone(&driver.control);
+    let interrupt = thread::spawn(move || {
+        while control.get_progress().0 < 100 {
+            thread::sleep(Duration::from_micros(100));
+        }
+
+        control.cancel();
+    });
+
+    assert!(matches!(driver.move_joint(Joint::Column, 800), Err(DriverError::Cancelled)));
+    interrupt.join().unwrap();
+
+    let steps = driver.get_step_count(Joint::Column);
+
+    // slowing down rather than stopping dead takes the braking steps on top of the 100
+    assert!((149..800).contains(&steps), "took {} steps", steps);
+    assert_eq!(column_trace.lock().unwrap().get_position(), -steps);
+    assert!(driver.homed);
+}
+
+#[test]
+fn emergency_stop_halts_and_needs_homing() {
+    let (mut driver, column_trace, _) = test_driver();
+    driver.micro_delay_default = 1000;
+    driver.tool = Some(Box::new(TestTool::new()));
+    driver.set_tool(true).unwrap();
+
+    let interrupt = interrupt_after(&driver.control, 50, true);
+    assert!(matches!(driver.move_joint(Joint::Column, 800), Err(DriverError::Stopped)));
+    interrupt.join().unwrap();
+
+    let steps = driver.get_step_count(Joint::Column);
+    assert!(steps > 0 && steps < 800);
+    assert_eq!(column_trace.lock().unwrap().get_position(), -steps);
+    assert!(!driver.homed);
+    assert!(!driver.tool.as_ref().unwrap().is_on());
+
+    // refused until the arm is homed again
+    assert!(matches!(driver.move_joint(Joint::Column, 10), Err(DriverError::NotHomed)));
+    driver.control.reset();
+    driver.home().unwrap();
+    driver.move_joint(Joint::Column, 10).unwrap();
+}
+
+#[test]
+fn a_panicked_motor_thread_is_an_error_and_needs_homing() {
+    let (mut driver, _, _) = test_driver();
+    assert!(driver.homed);
+
+    let thread = thread::spawn(|| -> i32 { panic!("the motor thread fell over") });
+
+    assert!(matches!(driver.join_step(thread), Err(DriverError::MotorPanicked)));
+    assert!(!driver.homed);
+}
+
+fn test_driver_3d() -> (Driver, [StepRecorder; 3]) {
+    let mut driver = Driver::new(Backend::Simulated, &Config::default()).unwrap();
+    let traces = Joint::all().map(|joint| get_trace(&driver, joint));
+    driver.micro_delay_default = 10;
+
+    return (driver, traces)
+}
+
+fn assert_on_steps(driver: &Driver, traces: &[StepRecorder; 3], expected: [i64; 3]) {
+    for (i, joint) in Joint::all().iter().enumerate() {
+        assert_eq!(driver.get_step_count(*joint), expected[i], "{:?}", joint);
+        assert_eq!(traces[i].lock().unwrap().get_position(), -expected[i], "{:?}", joint);
+    }
+}
+
+// targets worked out by hand with the default 1.0 links and 4000 steps a joint turn, 0.09 degrees a step.
+// counters are column, beam relative to the column, then base
+#[test]
+fn goto_3d_moves_each_axis_onto_its_own_steps() {
+    for coordinated in [true, false] {
+        let (mut driver, traces) = test_driver_3d();
+        driver.coordinated = coordinated;
+
+        // base atan(1/1) = 45, leaving 1.4142 1 in the arm's plane, sqrt(3) from the base. the links make an
+        // equilateral triangle with it so the beam is -60 and the column 35.26 + 30 = 65.26 elbow up
+        driver.goto_point_3d_with(1.0, 1.0, 1.0, ElbowPolicy::Up).unwrap();
+
+        assert_on_steps(&driver, &traces, [725, -667, 500]);
+        assert!(Calc::dist_3d(&driver.current_position, &Point { x: 1.0, y: 1.0, z: 1.0 }) < 0.05);
+
+        // base atan(-0.5/1) = -26.57, leaving 1.1180 0.5 in the plane, sqrt(1.5) out. the links open to 75.52
+        // degrees so the beam is -104.48 and the column 24.09 + 52.24 = 76.33. that's +123 column, -494 beam
+        // and the base coming back -795
+        driver.goto_point_3d_with(1.0, 0.5, -0.5, ElbowPolicy::Up).unwrap();
+
+        assert_on_steps(&driver, &traces, [848, -1161, -295]);
+        assert!(Calc::dist_3d(&driver.current_position, &Point { x: 1.0, y: 0.5, z: -0.5 }) < 0.05);
+    }
+}
+
+#[test]
+fn goto_3d_reach_is_from_the_base() {
+    let (mut driver, traces) = test_driver_3d();
+
+    // further than the arm is long from where it starts, but well inside its reach. elbow up behind the base
+    // has the column at 63.16 and the beam at 98.92
+    assert!(Calc::dist_3d(&driver.current_position, &Point { x: -0.5, y: 1.2, z: 0.0 }) > driver.calc.max_reach());
+    driver.goto_point_3d_with(-0.5, 1.2, 0.0, ElbowPolicy::Up).unwrap();
+
+    assert_on_steps(&driver, &traces, [702, 1099, 0]);
+
+    // close to where it is now, but out of reach
+    assert!(matches!(driver.goto_point_3d_with(2.0, 1.0, 0.0, ElbowPolicy::Up), Err(DriverError::UnReachable)));
+    assert_on_steps(&driver, &traces, [702, 1099, 0]);
+}
+
+#[test]
+fn plans_are_made_without_moving_anything() {
+    let (mut driver, column_trace, beam_trace) = test_driver();
+    driver.micro_delay_default = 10;
+
+    let plan = driver.plan_point(1.0, 1.0, ElbowPolicy::Up, None).unwrap();
+    let angles = driver.choose_solution(driver.calc.get_angle_solutions(1.0, 1.0), ElbowPolicy::Up);
+
+    assert_eq!(plan.steps, driver.get_steps_2d(angles.column_angle, angles.beam_angle));
+    assert_eq!(plan.steps.base, 0);
+    assert_eq!(column_trace.lock().unwrap().get_step_count(), 0);
+    assert_eq!(beam_trace.lock().unwrap().get_step_count(), 0);
+
+    // the snapped target is within a step of the point and the timing is one default delay per lead step
+    let target = driver.calc.get_pose(&plan.target).end_effector;
+    assert!(Calc::dist(target.x, target.y, 1.0, 1.0) < 0.02);
+    assert_eq!(plan.timing, PlanTiming::Coordinated(vec![10; plan.get_lead().1 as usize]));
+    assert_eq!(plan.get_duration(), Duration::from_micros(plan.get_lead().1 as u64 * 20));
+
+    driver.run_plan(&plan).unwrap();
+
+    assert_eq!(column_trace.lock().unwrap().get_position(), -plan.steps.column as i64);
+    assert_eq!(beam_trace.lock().unwrap().get_position(), -plan.steps.beam as i64);
+    assert_eq!(driver.get_angle_set(), plan.target);
+}
+
+#[test]
+fn uncoordinated_plans_time_each_joint_on_its_own() {
+    let (mut driver, _, _) = test_driver();
+    driver.coordinated = false;
+
+    let plan = driver.plan_point(1.0, 1.0, ElbowPolicy::Up, Some(ProfileKind::Trapezoidal)).unwrap();
+
+    match &plan.timing {
+        PlanTiming::Independent(delays) => {
+            for (i, joint) in Joint::all().iter().enumerate() {
+                assert_eq!(delays[i].len(), plan.steps.get(*joint).unsigned_abs() as usize);
+            }
+
+            assert!(plan.get_duration() >= delays.iter().map(|x| Duration::from_micros(x.iter().sum::<i64>() as u64)).max().unwrap());
+        },
+
+        PlanTiming::Coordinated(_) => panic!("expected independent timing")
+    }
+}
+
+#[test]
+fn a_plan_is_refused_once_the_arm_has_moved() {
+    let (mut driver, column_trace, _) = test_driver();
+    driver.micro_delay_default = 10;
+
+    let first = driver.plan_point(1.0, 1.0, ElbowPolicy::Up, None).unwrap();
+    let second = driver.plan_point(1.5, 0.5, ElbowPolicy::Up, None).unwrap();
+
+    driver.run_plan(&first).unwrap();
+
+    assert!(matches!(driver.run_plan(&second), Err(DriverError::StalePlan)));
+    assert_eq!(column_trace.lock().unwrap().get_position(), -first.steps.column as i64);
+
+    // planned again from here it goes
+    let second = driver.plan_point(1.5, 0.5, ElbowPolicy::Up, None).unwrap();
+    driver.run_plan(&second).unwrap();
+
+    assert!(Calc::dist(driver.current_position.x, driver.current_position.y, 1.5, 0.5) < 0.02);
+}
+
+#[test]
+fn targets_behind_the_base_are_reached_the_short_way() {
+    for policy in [ElbowPolicy::Up, ElbowPolicy::Down, ElbowPolicy::Nearest] {
+        let (mut driver, _, _) = test_driver();
+        driver.micro_delay_default = 10;
+
+        let plan = driver.plan_point(-1.5, -0.2, policy, None).unwrap();
+        let column = Calc::to_degree(plan.target.column_angle);
+        let beam = Calc::to_degree(plan.target.beam_angle) - column;
+
+        assert!(column.abs() <= 180.0 && beam.abs() <= 180.0, "{:?} {} {}", policy, column, beam);
+
+        driver.run_plan(&plan).unwrap();
+        assert!(Calc::dist(driver.current_position.x, driver.current_position.y, -1.5, -0.2) < 0.02);
+    }
+
+    // a path through there is planned the same way
+    let (driver, _, _) = test_driver();
+    assert!(driver.plan_path(&[Point { x: -1.5, y: -0.2, z: 0.0 }], 1.0).is_ok());
+}
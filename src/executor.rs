@@ -0,0 +1,230 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+use crate::driver::{Driver, MotionControl};
+
+// anything that needs the driver, run on the executor thread. Ok is a message for the operator
+pub type Job = Box<dyn FnOnce(&mut Driver) -> Result<String, String> + Send>;
+
+pub struct JobResult {
+    pub id: u64,
+    pub result: Result<String, String>
+}
+
+// owns the driver on its own thread and runs jobs off a queue one at a time, so whoever submits them
+// never waits on a move. cancel and emergency stop go straight to the stepping loops without the queue
+pub struct Executor {
+    driver: Arc<Mutex<Driver>>,
+    control: Arc<MotionControl>,
+    jobs: Sender<(u64, u64, Job)>,
+    results: Receiver<JobResult>,
+    next_id: u64,
+    generation: Arc<AtomicU64>, // bumped by cancel and stop, anything queued before is dropped
+    stopped: Arc<AtomicBool>, // an emergency stop the worker hasn't marked on the driver yet
+    pending: Arc<AtomicUsize>
+}
+
+impl Executor {
+    pub fn new(driver: Driver) -> Executor {
+        let control = Arc::clone(&driver.control);
+        let driver = Arc::new(Mutex::new(driver));
+        let generation = Arc::new(AtomicU64::new(0));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        let (jobs, job_rx) = mpsc::channel::<(u64, u64, Job)>();
+        let (result_tx, results) = mpsc::channel();
+
+        let worker_driver = Arc::clone(&driver);
+        let worker_control = Arc::clone(&control);
+        let worker_generation = Arc::clone(&generation);
+        let worker_stopped = Arc::clone(&stopped);
+        let worker_pending = Arc::clone(&pending);
+
+        thread::spawn(move || {
+            for (id, job_generation, job) in job_rx {
+                let mut driver = worker_driver.lock().unwrap();
+
+                if worker_stopped.swap(false, Ordering::SeqCst) { driver.mark_stopped() }
+
+                // cleared before the generation check, so a cancel that lands in between still drops the job
+                worker_control.reset();
+
+                let result = if job_generation != worker_generation.load(Ordering::SeqCst) {
+                    Err(String::from("dropped, cancelled before it started"))
+                } else {
+                    job(&mut driver)
+                };
+
+                if worker_control.is_stopped() { driver.mark_stopped() }
+
+                drop(driver);
+                worker_pending.fetch_sub(1, Ordering::SeqCst);
+
+                if result_tx.send(JobResult { id, result }).is_err() { break }
+            }
+        });
+
+        return Executor { driver, control, jobs, results, next_id: 0, generation, stopped, pending }
+    }
+
+    // returns the id its result will come back with
+    pub fn submit(&mut self, job: Job) -> u64 {
+        self.next_id += 1;
+        self.pending.fetch_add(1, Ordering::SeqCst);
+
+        if self.jobs.send((self.next_id, self.generation.load(Ordering::SeqCst), job)).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        return self.next_id
+    }
+
+    // results of every job finished since the last call
+    pub fn poll(&self) -> Vec<JobResult> {
+        return self.results.try_iter().collect()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        return self.pending.load(Ordering::SeqCst) > 0
+    }
+
+    // None while a job has it
+    pub fn try_driver(&self) -> Option<MutexGuard<'_, Driver>> {
+        return self.driver.try_lock().ok()
+    }
+
+    // waits for the queue to empty, for callers that want everything done before carrying on
+    #[cfg(test)]
+    pub fn wait(&self) -> MutexGuard<'_, Driver> {
+        while self.is_busy() {
+            thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        return self.driver.lock().unwrap()
+    }
+
+    // brakes whatever is moving to a stop and drops everything queued behind it
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.control.cancel();
+    }
+
+    // no more pulses or tool, and the position isn't trusted again until the arm is homed
+    pub fn emergency_stop(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.control.emergency_stop();
+        self.stopped.store(true, Ordering::SeqCst);
+
+        if let Some(mut driver) = self.try_driver() {
+            driver.mark_stopped();
+        }
+    }
+
+    // steps done and planned in the move running now, None when idle
+    pub fn get_progress(&self) -> Option<(u64, u64)> {
+        if !self.is_busy() { return None }
+
+        return Some(self.control.get_progress())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::driver::{Backend, DriverError, Joint};
+    use crate::tool::TestTool;
+    use std::time::{Duration, Instant};
+
+    fn test_executor() -> Executor {
+        let mut driver = Driver::new(Backend::Simulated, &Config::default()).unwrap();
+        driver.micro_delay_default = 1000;
+        driver.acceleration = 10_000.0 * driver.column_step_degree;
+        driver.tool = Some(Box::new(TestTool::new()));
+
+        return Executor::new(driver)
+    }
+
+    fn wait_for(executor: &Executor, count: usize) -> Vec<JobResult> {
+        let start = Instant::now();
+        let mut results = Vec::new();
+
+        while results.len() < count && start.elapsed() < Duration::from_secs(5) {
+            results.extend(executor.poll());
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        return results
+    }
+
+    fn move_column(steps: i32) -> Job {
+        return Box::new(move |driver| driver.move_joint(Joint::Column, steps).map(|_| format!("{}", steps)).map_err(|e| e.get_message()))
+    }
+
+    #[test]
+    fn jobs_run_in_order_without_blocking() {
+        let mut executor = test_executor();
+
+        let first = executor.submit(move_column(100));
+        let second = executor.submit(move_column(-40));
+        assert!(executor.is_busy());
+
+        let results = wait_for(&executor, 2);
+        let ids: Vec<u64> = results.iter().map(|x| x.id).collect();
+
+        assert_eq!(ids, vec![first, second]);
+        assert_eq!(results[1].result, Ok(String::from("-40")));
+        assert_eq!(executor.wait().get_step_count(Joint::Column), 60);
+    }
+
+    #[test]
+    fn cancel_drops_what_is_queued() {
+        let mut executor = test_executor();
+
+        executor.submit(move_column(800));
+        executor.submit(move_column(10));
+        thread::sleep(Duration::from_millis(50));
+        executor.cancel();
+
+        let results = wait_for(&executor, 2);
+
+        assert_eq!(results[0].result, Err(DriverError::Cancelled.get_message()));
+        assert!(results[1].result.is_err());
+
+        // the next job starts with the flags cleared
+        let steps = executor.wait().get_step_count(Joint::Column);
+        executor.submit(move_column(10));
+
+        assert!(wait_for(&executor, 1)[0].result.is_ok());
+        assert_eq!(executor.wait().get_step_count(Joint::Column), steps + 10);
+    }
+
+    fn is_tool_on(executor: &Executor) -> bool {
+        return executor.wait().tool.as_ref().unwrap().is_on()
+    }
+
+    #[test]
+    fn emergency_stop_leaves_the_arm_unhomed() {
+        let mut executor = test_executor();
+        executor.wait().set_tool(true).unwrap();
+
+        executor.submit(move_column(800));
+        thread::sleep(Duration::from_millis(50));
+        executor.emergency_stop();
+
+        assert_eq!(wait_for(&executor, 1)[0].result, Err(DriverError::Stopped.get_message()));
+        assert!(!executor.wait().homed);
+        assert!(!is_tool_on(&executor));
+
+        // with nothing moving the tool goes off straight away
+        executor.wait().set_tool(true).unwrap();
+        executor.emergency_stop();
+        assert!(!is_tool_on(&executor));
+
+        executor.submit(move_column(10));
+        assert_eq!(wait_for(&executor, 1)[0].result, Err(DriverError::NotHomed.get_message()));
+    }
+}
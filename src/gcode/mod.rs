@@ -0,0 +1,213 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::driver::{Driver, DriverError, ArcShape};
+use crate::utils::Point;
+
+mod parser;
+
+pub use parser::{Block, parse_line};
+
+#[cfg(test)]
+mod tests;
+
+// anything longer is taken for a typo rather than slept through
+const MAX_DWELL_SECONDS: f32 = 3600.0;
+
+// how long a dwell sleeps between looking for a cancel
+const DWELL_SLICE: Duration = Duration::from_millis(10);
+
+// every variant carries the line it happened on
+#[derive(Debug)]
+pub enum GcodeError {
+    Unreadable(String, String),
+    Parse(usize, String),
+    Unsupported(usize, String),
+    Driver(usize, DriverError)
+}
+
+impl GcodeError {
+    pub fn get_message(&self) -> String {
+        match self {
+            GcodeError::Unreadable(path, e) => format!("unable to read g-code {}: {}", path, e),
+            GcodeError::Parse(line, e) => format!("line {}: {}", line, e),
+            GcodeError::Unsupported(line, e) => format!("line {}: {} is not supported", line, e),
+            GcodeError::Driver(line, e) => format!("line {}: {}", line, e.get_message())
+        }
+    }
+}
+
+// what the program has set so far. coordinates are in the same units as the link lengths and
+// feeds are per minute like any other g-code, the arm itself works in units a second. every motion
+// word is in the world frame, which stays put whichever way the base is turned
+pub struct Interpreter {
+    pub absolute: bool,
+    pub feed: f32,
+    pub motion: Option<u8>,
+    pub finished: bool
+}
+
+impl Interpreter {
+    pub fn new(driver: &Driver) -> Interpreter {
+        return Interpreter { absolute: true, feed: driver.tool_speed * 60.0, motion: None, finished: false }
+    }
+
+    pub fn load(path: &str) -> Result<String, GcodeError> {
+        return match std::fs::read_to_string(path) {
+            Ok(x) => Ok(x),
+            Err(e) => Err(GcodeError::Unreadable(path.to_string(), format!("{}", e)))
+        }
+    }
+
+    // runs until the end of the program or an M2/M30, returns how many blocks ran. a program that fails or is
+    // cancelled never gets to its M5, so the tool is switched off on the way out
+    pub fn run(&mut self, driver: &mut Driver, program: &str) -> Result<usize, GcodeError> {
+        let result = self.run_blocks(driver, program);
+
+        if result.is_err() { driver.switch_tool_off() }
+
+        return result
+    }
+
+    fn run_blocks(&mut self, driver: &mut Driver, program: &str) -> Result<usize, GcodeError> {
+        let mut count = 0;
+
+        for (i, text) in program.lines().enumerate() {
+            if self.finished { break }
+
+            let block = match parse_line(i + 1, text)? {
+                Some(x) => x,
+                None => continue
+            };
+
+            self.execute(driver, &block)?;
+            count += 1;
+        }
+
+        return Ok(count)
+    }
+
+    pub fn execute(&mut self, driver: &mut Driver, block: &Block) -> Result<(), GcodeError> {
+        let line = block.line;
+
+        for word in &block.words {
+            if !"GMXYZFIJRPSN".contains(word.letter) {
+                return Err(GcodeError::Unsupported(line, format!("{}{}", word.letter, word.value)))
+            }
+        }
+
+        if let Some(feed) = block.get('F') {
+            if feed <= 0.0 { return Err(GcodeError::Parse(line, format!("F{} has to be above 0", feed))) }
+
+            self.feed = feed;
+        }
+
+        for code in block.get_codes('G') {
+            match Interpreter::get_code(line, 'G', code)? {
+                0..=3 => self.motion = Some(code as u8),
+                4 => self.dwell(driver, block)?,
+                28 => driver.home().map_err(|e| GcodeError::Driver(line, e))?,
+                90 => self.absolute = true,
+                91 => self.absolute = false,
+                _ => return Err(GcodeError::Unsupported(line, format!("G{}", code)))
+            }
+        }
+
+        for code in block.get_codes('M') {
+            let result = match Interpreter::get_code(line, 'M', code)? {
+                3 | 4 => driver.set_tool(true),
+                5 => driver.set_tool(false),
+                2 | 30 => { self.finished = true; driver.switch_tool_off(); Ok(()) },
+                _ => return Err(GcodeError::Unsupported(line, format!("M{}", code)))
+            };
+
+            result.map_err(|e| GcodeError::Driver(line, e))?;
+        }
+
+        let moves = ['X', 'Y', 'Z', 'I', 'J', 'R'].iter().any(|x| block.get(*x).is_some());
+        let dwells = block.get_codes('G').contains(&4.0);
+
+        if !moves || dwells { return Ok(()) }
+
+        let result = match self.motion {
+            Some(0) => {
+                let target = self.get_target(block, &driver.current_position);
+                driver.goto_joint(target.x, target.y, target.z)
+            },
+
+            Some(1) => {
+                let target = self.get_target(block, &driver.current_position);
+                driver.goto_line_3d(target.x, target.y, target.z, self.feed / 60.0)
+            },
+
+            Some(motion) => {
+                let shape = self.get_arc_shape(block, &driver.current_position)?;
+                let target = self.get_target(block, &driver.current_position);
+
+                driver.goto_arc_3d(target.x, target.y, shape, motion == 2, self.feed / 60.0)
+            },
+
+            None => return Err(GcodeError::Parse(line, String::from("coordinates without a G0, G1, G2 or G3 before them")))
+        };
+
+        return result.map_err(|e| GcodeError::Driver(line, e))
+    }
+
+    fn get_code(line: usize, letter: char, value: f32) -> Result<u32, GcodeError> {
+        if value < 0.0 || value.fract() != 0.0 {
+            return Err(GcodeError::Unsupported(line, format!("{}{}", letter, value)))
+        }
+
+        return Ok(value as u32)
+    }
+
+    // P is milliseconds and S seconds. it sleeps a slice at a time so a cancel or stop ends it like a move
+    fn dwell(&self, driver: &Driver, block: &Block) -> Result<(), GcodeError> {
+        let seconds = block.get('P').map(|x| x / 1000.0).or(block.get('S')).unwrap_or(0.0);
+
+        if !seconds.is_finite() || seconds > MAX_DWELL_SECONDS {
+            return Err(GcodeError::Parse(block.line, format!("dwell of {}s, it can be at most {}s", seconds, MAX_DWELL_SECONDS)))
+        }
+
+        let end = Instant::now() + Duration::from_secs_f32(f32::max(0.0, seconds));
+
+        while Instant::now() < end {
+            if driver.control.is_stopped() { return Err(GcodeError::Driver(block.line, DriverError::Stopped)) }
+            if driver.control.is_cancelled() { return Err(GcodeError::Driver(block.line, DriverError::Cancelled)) }
+
+            thread::sleep(Duration::min(DWELL_SLICE, end.saturating_duration_since(Instant::now())));
+        }
+
+        Ok(())
+    }
+
+    // axes left out stay where they are, G91 makes the ones given offsets from there
+    fn get_target(&self, block: &Block, from: &Point) -> Point {
+        let axis = |letter: char, current: f32| match block.get(letter) {
+            Some(x) if self.absolute => x,
+            Some(x) => current + x,
+            None => current
+        };
+
+        return Point { x: axis('X', from.x), y: axis('Y', from.y), z: axis('Z', from.z) }
+    }
+
+    // arcs are in the x y plane at the z the tool is at. I and J are always offsets from the start, R is the radius
+    fn get_arc_shape(&self, block: &Block, from: &Point) -> Result<ArcShape, GcodeError> {
+        if block.get('Z').is_some() {
+            return Err(GcodeError::Unsupported(block.line, String::from("Z on an arc")))
+        }
+
+        if let Some(radius) = block.get('R') {
+            return Ok(ArcShape::Radius(radius))
+        }
+
+        let (i, j) = (block.get('I').unwrap_or(0.0), block.get('J').unwrap_or(0.0));
+
+        if i == 0.0 && j == 0.0 {
+            return Err(GcodeError::Parse(block.line, String::from("arcs need I and J or R")))
+        }
+
+        return Ok(ArcShape::Center(from.x + i, from.y + j))
+    }
+}
@@ -0,0 +1,74 @@
+use crate::gcode::GcodeError;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Word {
+    pub letter: char,
+    pub value: f32
+}
+
+// one line of a program, line is counted from 1 for error messages
+#[derive(Clone, PartialEq, Debug)]
+pub struct Block {
+    pub line: usize,
+    pub words: Vec<Word>
+}
+
+impl Block {
+    pub fn get(&self, letter: char) -> Option<f32> {
+        return self.words.iter().find(|x| x.letter == letter).map(|x| x.value)
+    }
+
+    pub fn get_codes(&self, letter: char) -> Vec<f32> {
+        return self.words.iter().filter(|x| x.letter == letter).map(|x| x.value).collect()
+    }
+}
+
+// None for lines with nothing but comments, whitespace or a % tape marker
+pub fn parse_line(line: usize, text: &str) -> Result<Option<Block>, GcodeError> {
+    let mut words: Vec<Word> = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => break,
+
+            '(' => {
+                if !chars.any(|x| x == ')') {
+                    return Err(GcodeError::Parse(line, String::from("comment is never closed")))
+                }
+            },
+
+            '%' => (),
+
+            c if c.is_whitespace() => (),
+
+            c if c.is_ascii_alphabetic() => {
+                let mut number = String::new();
+
+                while let Some(next) = chars.peek() {
+                    if next.is_ascii_digit() || *next == '.' || *next == '-' || *next == '+' {
+                        number.push(*next);
+                        chars.next();
+                    } else if next.is_whitespace() && number.is_empty() {
+                        chars.next();
+                    } else {
+                        break
+                    }
+                }
+
+                let value = match number.parse::<f32>() {
+                    Ok(x) => x,
+                    Err(_) => return Err(GcodeError::Parse(line, format!("{} needs a number, got '{}'", c, number)))
+                };
+
+                words.push(Word { letter: c.to_ascii_uppercase(), value });
+            },
+
+            _ => return Err(GcodeError::Parse(line, format!("unexpected '{}'", c)))
+        }
+    }
+
+    if words.is_empty() { return Ok(None) }
+
+    Ok(Some(Block { line, words }))
+}
@@ -0,0 +1,137 @@
+use super::*;
+use crate::calc::Calc;
+use crate::config::Config;
+use crate::driver::{Backend, Joint};
+use crate::utils::Point;
+use crate::tool::TestTool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn test_driver() -> Driver {
+    let mut driver = Driver::new(Backend::Simulated, &Config::default()).unwrap();
+    driver.micro_delay_default = 10;
+    driver.max_velocity = 100_000.0;
+    driver.tool = Some(Box::new(TestTool::new()));
+
+    return driver
+}
+
+#[test]
+fn parser_reads_words_without_spaces_and_skips_comments() {
+    let block = parse_line(3, "n10 G1X1.5 Y-0.25 (move out) F 600 ; done").unwrap().unwrap();
+    let letters: Vec<char> = block.words.iter().map(|x| x.letter).collect();
+
+    assert_eq!(block.line, 3);
+    assert_eq!(letters, vec!['N', 'G', 'X', 'Y', 'F']);
+    assert_eq!(block.get('Y'), Some(-0.25));
+    assert_eq!(block.get('F'), Some(600.0));
+
+    assert_eq!(parse_line(1, "  ; just a comment").unwrap(), None);
+    assert_eq!(parse_line(1, "%").unwrap(), None);
+    assert!(matches!(parse_line(4, "G1 X"), Err(GcodeError::Parse(4, _))));
+    assert!(matches!(parse_line(5, "G1 (open"), Err(GcodeError::Parse(5, _))));
+}
+
+#[test]
+fn programs_move_the_arm_and_switch_the_tool() {
+    let mut driver = test_driver();
+    let mut interpreter = Interpreter::new(&driver);
+    let program = "G90\nG0 X1.5 Y0.5\nM3\nG1 X1.3 Y0.9 F3000\nG91\nG1 Y-0.2\nM5\nM2\nG0 X0 Y0";
+
+    let blocks = interpreter.run(&mut driver, program).unwrap();
+    let position = &driver.current_position;
+
+    // nothing after the M2 runs
+    assert_eq!(blocks, 8);
+    assert!(Calc::dist(position.x, position.y, 1.3, 0.7) < 5e-3, "ended at {} {}", position.x, position.y);
+    assert!(!driver.tool.as_ref().unwrap().is_on());
+    assert!(!interpreter.absolute);
+    assert_eq!(interpreter.feed, 3000.0);
+}
+
+#[test]
+fn arcs_take_the_center_as_an_offset() {
+    let mut driver = test_driver();
+    let mut interpreter = Interpreter::new(&driver);
+
+    interpreter.run(&mut driver, "G0 X1.5 Y0.5\nG3 X1 Y1 I-0.5 J0 F3000").unwrap();
+
+    assert!(Calc::dist(driver.current_position.x, driver.current_position.y, 1.0, 1.0) < 5e-3);
+}
+
+#[test]
+fn arcs_and_lines_share_a_frame_with_the_base_turned() {
+    let mut driver = test_driver();
+    let mut interpreter = Interpreter::new(&driver);
+    driver.set_step_count(Joint::Base, driver.get_target_steps(Joint::Base, 30.0));
+
+    // out of the arm's plane, then a quarter circle of radius 0.2 round 1.0 0.6 at the same z
+    interpreter.run(&mut driver, "G1 X1.2 Y0.6 Z0.4 F3000\nG3 X1 Y0.8 I-0.2 J0").unwrap();
+
+    let position = &driver.current_position;
+    assert!(Calc::dist_3d(position, &Point { x: 1.0, y: 0.8, z: 0.4 }) < 5e-3, "ended at {} {} {}", position.x, position.y, position.z);
+}
+
+#[test]
+fn errors_carry_their_line() {
+    let mut driver = test_driver();
+
+    let result = Interpreter::new(&driver).run(&mut driver, "G0 X1.5 Y0.5\n\nG17");
+    assert!(matches!(result, Err(GcodeError::Unsupported(3, _))));
+
+    let result = Interpreter::new(&driver).run(&mut driver, "X1 Y1");
+    assert!(matches!(result, Err(GcodeError::Parse(1, _))));
+
+    let result = Interpreter::new(&driver).run(&mut driver, "G1 X5 Y5");
+    assert!(matches!(result, Err(GcodeError::Driver(1, DriverError::UnReachable))));
+
+    driver.tool = None;
+    let result = Interpreter::new(&driver).run(&mut driver, "M3");
+    assert!(matches!(result, Err(GcodeError::Driver(1, DriverError::NoTool))));
+}
+
+#[test]
+fn the_tool_is_off_however_a_program_ends() {
+    let mut driver = test_driver();
+
+    Interpreter::new(&driver).run(&mut driver, "M3\nM2").unwrap();
+    assert!(!driver.tool.as_ref().unwrap().is_on());
+
+    // the tool is on when the third line fails
+    let result = Interpreter::new(&driver).run(&mut driver, "G0 X1.5 Y0.5\nM3\nG1 X5 Y5\nM5");
+    assert!(matches!(result, Err(GcodeError::Driver(3, DriverError::UnReachable))));
+    assert!(!driver.tool.as_ref().unwrap().is_on());
+
+    driver.control.cancel();
+    let result = Interpreter::new(&driver).run(&mut driver, "M3\nG4 S1");
+    assert!(matches!(result, Err(GcodeError::Driver(2, DriverError::Cancelled))));
+    assert!(!driver.tool.as_ref().unwrap().is_on());
+}
+
+#[test]
+fn dwells_are_bounded_and_can_be_cancelled() {
+    let mut driver = test_driver();
+
+    for program in ["G4 S100000000000000000000000", "G4 P1000000000000000000000000000000000000000000000", "G4 S3601"] {
+        let result = Interpreter::new(&driver).run(&mut driver, program);
+        assert!(matches!(result, Err(GcodeError::Parse(1, _))), "{} {:?}", program, result);
+    }
+
+    let started = Instant::now();
+    Interpreter::new(&driver).run(&mut driver, "G4 P20").unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(20));
+
+    // a cancel part way through a long dwell ends it
+    let control = Arc::clone(&driver.control);
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(30));
+        control.cancel();
+    });
+
+    let started = Instant::now();
+    let result = Interpreter::new(&driver).run(&mut driver, "G4 S60");
+    canceller.join().unwrap();
+
+    assert!(matches!(result, Err(GcodeError::Driver(1, DriverError::Cancelled))));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,263 @@
+use crate::config::Config;
+use crate::driver::{Backend, Driver, Joint};
+use crate::gcode::Interpreter;
+use crate::program::{Program, Replay};
+
+// exit codes, a command that ran and failed is told apart from one that never got to run
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILED: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+pub const USAGE: &str = "usage: temp2 [--sim] [--config <path>] [<command> ...]
+commands run in order and stop at the first failure:
+  goto <x> <y> [z]            move there, turning the base when z is given
+  move-motor <joint> <steps>  step the column, beam or base directly
+  run <file>                  run a g-code file
+  replay <file> [loops]       replay a taught program, once unless told otherwise, 0 loops until killed
+  home                        home every axis with a switch
+  status                      print the angles, position and step counts
+with no command the interface starts";
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    Goto(f32, f32, Option<f32>),
+    MoveMotor(Joint, i32),
+    Run(String),
+    Replay(String, u32),
+    Home,
+    Status
+}
+
+impl Command {
+    // commands can be chained, `home goto 1 1 status`, since every process starts from wherever the
+    // step counters begin
+    pub fn parse_all(args: &[String]) -> Result<Vec<Command>, String> {
+        let mut commands: Vec<Command> = Vec::new();
+        let mut i = 0;
+
+        while i < args.len() {
+            let verb = args[i].as_str();
+            i += 1;
+
+            let command = match verb {
+                "goto" => {
+                    let mut numbers: Vec<f32> = Vec::new();
+
+                    while numbers.len() < 3 {
+                        match args.get(i).map(|x| x.parse::<f32>()) {
+                            Some(Ok(x)) => numbers.push(x),
+                            _ => break
+                        }
+
+                        i += 1;
+                    }
+
+                    if numbers.len() < 2 { return Err(String::from("goto needs x y and optionally z")) }
+
+                    Command::Goto(numbers[0], numbers[1], numbers.get(2).copied())
+                },
+
+                "move-motor" => {
+                    let joint = match args.get(i).and_then(|x| Joint::parse(x)) {
+                        Some(x) => x,
+                        None => return Err(String::from("move-motor needs column, beam or base"))
+                    };
+
+                    let steps = match args.get(i + 1).map(|x| x.parse::<i32>()) {
+                        Some(Ok(x)) => x,
+                        _ => return Err(String::from("move-motor needs a whole number of steps"))
+                    };
+
+                    i += 2;
+                    Command::MoveMotor(joint, steps)
+                },
+
+                "run" | "--run" => {
+                    let path = match args.get(i) {
+                        Some(x) => x.clone(),
+                        None => return Err(String::from("run needs a g-code file"))
+                    };
+
+                    i += 1;
+                    Command::Run(path)
+                },
+
+                "replay" => {
+                    let path = match args.get(i) {
+                        Some(x) => x.clone(),
+                        None => return Err(String::from("replay needs a program file"))
+                    };
+
+                    i += 1;
+
+                    let loops = match args.get(i).map(|x| x.parse::<u32>()) {
+                        Some(Ok(x)) => { i += 1; x },
+                        _ => 1
+                    };
+
+                    Command::Replay(path, loops)
+                },
+
+                "home" => Command::Home,
+
+                "status" => Command::Status,
+
+                _ => return Err(format!("unknown command '{}'", verb))
+            };
+
+            commands.push(command);
+        }
+
+        return Ok(commands)
+    }
+
+    pub fn execute(&self, driver: &mut Driver) -> Result<String, String> {
+        match self {
+            Command::Goto(x, y, z) => {
+                let result = match z {
+                    Some(z) => driver.goto_joint(*x, *y, *z),
+                    None => driver.goto_point(*x, *y)
+                };
+
+                result.map_err(|e| e.get_message())?;
+
+                Ok(format!("moved to {}", get_position(driver)))
+            },
+
+            Command::MoveMotor(joint, steps) => {
+                driver.move_joint(*joint, *steps).map_err(|e| e.get_message())?;
+
+                Ok(format!("moved {} {} steps, now at {:.2} degrees", joint.get_name(), steps, get_angle(driver, *joint)))
+            },
+
+            Command::Run(path) => {
+                let program = Interpreter::load(path).map_err(|e| e.get_message())?;
+                let blocks = Interpreter::new(driver).run(driver, &program).map_err(|e| e.get_message())?;
+
+                Ok(format!("ran {} blocks from {}, ended at {}", blocks, path, get_position(driver)))
+            },
+
+            Command::Replay(path, loops) => {
+                let program = Program::load(path).map_err(|e| e.get_message())?;
+                let mut replay = Replay::new(program, *loops);
+                let waypoints = replay.run(driver).map_err(|e| e.get_message())?;
+
+                Ok(format!("replayed {} waypoints from {}, ended at {}", waypoints, path, get_position(driver)))
+            },
+
+            Command::Home => {
+                driver.home().map_err(|e| e.get_message())?;
+
+                Ok(format!("homed, now at {}", get_position(driver)))
+            },
+
+            Command::Status => Ok(get_status(driver))
+        }
+    }
+}
+
+// everything goes to stdout so scripts can capture the whole run, the exit code tells how it went
+pub fn run(backend: Backend, config_path: &str, commands: &[Command]) -> i32 {
+    let config = match Config::load(config_path) {
+        Ok(x) => x,
+        Err(e) => {
+            for message in e.get_messages() {
+                println!("{}", message);
+            }
+
+            return EXIT_USAGE
+        }
+    };
+
+    let mut driver = match Driver::new(backend, &config) {
+        Ok(x) => x,
+        Err(e) => {
+            println!("unable to start driver: {}", e.get_message());
+            return EXIT_USAGE
+        }
+    };
+
+    for command in commands {
+        match command.execute(&mut driver) {
+            Ok(message) => println!("{}", message),
+            Err(e) => {
+                println!("{}", e);
+                return EXIT_FAILED
+            }
+        }
+    }
+
+    return EXIT_OK
+}
+
+fn get_position(driver: &Driver) -> String {
+    let position = &driver.current_position;
+
+    return format!("{:.4} {:.4} {:.4}", position.x, position.y, position.z)
+}
+
+fn get_angle(driver: &Driver, joint: Joint) -> f32 {
+    match joint {
+        Joint::Column => driver.get_column_angle(),
+        Joint::Beam => driver.get_beam_angle(),
+        Joint::Base => driver.get_base_angle()
+    }
+}
+
+fn get_status(driver: &Driver) -> String {
+    let mut lines = vec![
+        format!("motors: {}", driver.describe_motors()),
+        format!("homed: {}", driver.homed),
+        format!("position: {}", get_position(driver))
+    ];
+
+    for joint in Joint::all() {
+        lines.push(format!("{}: {:.2} degrees, {} steps", joint.get_name(), get_angle(driver, joint), driver.get_step_count(joint)));
+    }
+
+    lines.push(format!("tool: {}", match driver.tool.as_ref() {
+        Some(tool) => if tool.is_on() { "on" } else { "off" },
+        None => "none"
+    }));
+
+    return lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        return line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn commands_chain_and_take_their_arguments() {
+        let commands = Command::parse_all(&args("home goto 1.5 0.5 goto 1 1 0.2 move-motor Beam -20 run a.gcode replay b.txt 3 replay c.txt status")).unwrap();
+
+        assert_eq!(commands, vec![Command::Home, Command::Goto(1.5, 0.5, None), Command::Goto(1.0, 1.0, Some(0.2)),
+                                  Command::MoveMotor(Joint::Beam, -20), Command::Run(String::from("a.gcode")),
+                                  Command::Replay(String::from("b.txt"), 3), Command::Replay(String::from("c.txt"), 1), Command::Status]);
+    }
+
+    #[test]
+    fn bad_commands_are_refused_before_anything_runs() {
+        assert!(Command::parse_all(&args("goto 1")).is_err());
+        assert!(Command::parse_all(&args("move-motor elbow 20")).is_err());
+        assert!(Command::parse_all(&args("move-motor beam 2.5")).is_err());
+        assert!(Command::parse_all(&args("run")).is_err());
+        assert!(Command::parse_all(&args("home jump")).is_err());
+    }
+
+    #[test]
+    fn commands_report_what_they_did() {
+        let mut driver = Driver::new(Backend::Simulated, &Config::default()).unwrap();
+        driver.micro_delay_default = 10;
+
+        assert!(Command::Goto(1.5, 0.5, None).execute(&mut driver).unwrap().starts_with("moved to"));
+        assert!(Command::MoveMotor(Joint::Base, 30).execute(&mut driver).is_ok());
+        assert_eq!(driver.get_step_count(Joint::Base), 30);
+        assert!(Command::Goto(5.0, 5.0, None).execute(&mut driver).is_err());
+        assert!(Command::Status.execute(&mut driver).unwrap().contains("base: "));
+    }
+}
@@ -3,9 +3,67 @@ mod driver;
 mod calc;
 mod app;
 mod stepper;
+mod config;
+mod switch;
+mod profile;
+mod tool;
+mod gcode;
+mod headless;
+mod program;
+mod executor;
+
+use driver::Backend;
 
 fn main() {
-    let mut main = app::App::new();
+    let args: Vec<String> = std::env::args().collect();
+
+    let backend = if args.iter().any(|arg| arg == "--sim") { Backend::Simulated } else { Backend::Hardware };
+
+    let config_path = match args.iter().position(|arg| arg == "--config") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) => path.clone(),
+            None => {
+                eprintln!("--config needs a path");
+                std::process::exit(headless::EXIT_USAGE)
+            }
+        },
+
+        None => String::from(config::DEFAULT_PATH)
+    };
+
+    // whatever isn't a flag is a command to run without the interface
+    let commands: Vec<String> = args.iter().enumerate().skip(1)
+        .filter(|(i, arg)| *arg != "--sim" && *arg != "--config" && args[i - 1] != "--config")
+        .map(|(_, arg)| arg.clone()).collect();
+
+    if commands.iter().any(|arg| arg == "--help" || arg == "help") {
+        println!("{}", headless::USAGE);
+        std::process::exit(headless::EXIT_OK)
+    }
+
+    if !commands.is_empty() {
+        let commands = match headless::Command::parse_all(&commands) {
+            Ok(x) => x,
+            Err(e) => {
+                println!("{}\n{}", e, headless::USAGE);
+                std::process::exit(headless::EXIT_USAGE)
+            }
+        };
+
+        std::process::exit(headless::run(backend, &config_path, &commands))
+    }
+
+    let mut main = match app::App::new(backend, &config_path) {
+        Ok(x) => x,
+        Err(messages) => {
+            for message in messages {
+                eprintln!("{}", message);
+            }
+
+            eprintln!("run with --sim to use simulated motors");
+            std::process::exit(1)
+        }
+    };
 
     main.start();
 }
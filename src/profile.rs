@@ -0,0 +1,267 @@
+use crate::calc::Calc;
+
+pub trait MotionProfile {
+    // microseconds to wait after each of `steps` steps, every step turns the joint step_degree degrees
+    fn get_delays(&self, steps: u32, step_degree: f32) -> Vec<i64>;
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ProfileKind {
+    Polynomial,
+    Trapezoidal,
+    SCurve
+}
+
+impl ProfileKind {
+    pub fn parse(name: &str) -> Option<ProfileKind> {
+        match name {
+            "poly" | "polynomial" => Some(ProfileKind::Polynomial),
+            "trap" | "trapezoidal" => Some(ProfileKind::Trapezoidal),
+            "scurve" | "s-curve" => Some(ProfileKind::SCurve),
+            _ => None
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            ProfileKind::Polynomial => "poly",
+            ProfileKind::Trapezoidal => "trap",
+            ProfileKind::SCurve => "scurve"
+        }
+    }
+}
+
+// the original smooth move, an 8th power curve squeezed between the min and max delay.
+// it doesn't care how far a step turns the joint
+pub struct Polynomial {
+    pub min_delay: i64,
+    pub max_delay: i64
+}
+
+// constant acceleration up to max_velocity and back down, velocities in degrees a second
+pub struct Trapezoidal {
+    pub max_velocity: f32,
+    pub acceleration: f32,
+    pub min_delay: i64
+}
+
+// like the trapezoid but the acceleration itself ramps at `jerk` degrees a second cubed
+pub struct SCurve {
+    pub max_velocity: f32,
+    pub acceleration: f32,
+    pub jerk: f32,
+    pub min_delay: i64
+}
+
+impl MotionProfile for Polynomial {
+    fn get_delays(&self, steps: u32, _step_degree: f32) -> Vec<i64> {
+        if steps == 0 { return Vec::new() }
+
+        let linear = (1..=steps as i64).collect();
+
+        // a single step normalizes to nothing, give it the min delay instead
+        return match Calc::normalize_vec(self.min_delay, self.max_delay, Calc::smooth(linear)) {
+            Some(x) => x.into_iter().map(|x| i64::max(x, self.min_delay)).collect(),
+            None => Vec::new()
+        }
+    }
+}
+
+impl MotionProfile for Trapezoidal {
+    fn get_delays(&self, steps: u32, step_degree: f32) -> Vec<i64> {
+        let ramp = Ramp { max_velocity: self.max_velocity as f64, acceleration: self.acceleration as f64, jerk: f64::INFINITY };
+
+        return ramp.get_delays(steps, step_degree, self.min_delay)
+    }
+}
+
+impl MotionProfile for SCurve {
+    fn get_delays(&self, steps: u32, step_degree: f32) -> Vec<i64> {
+        let ramp = Ramp { max_velocity: self.max_velocity as f64, acceleration: self.acceleration as f64, jerk: self.jerk as f64 };
+
+        return ramp.get_delays(steps, step_degree, self.min_delay)
+    }
+}
+
+// a symmetric ramp up, cruise and ramp down. infinite jerk makes it a plain trapezoid
+struct Ramp {
+    max_velocity: f64,
+    acceleration: f64,
+    jerk: f64
+}
+
+impl Ramp {
+    // ramps too short to reach the full acceleration before they have to ease off again
+    fn get_peak_acceleration(&self, velocity: f64) -> f64 {
+        return f64::min(self.acceleration, f64::sqrt(velocity * self.jerk))
+    }
+
+    // seconds from standstill to `velocity`
+    fn get_ramp_time(&self, velocity: f64) -> f64 {
+        let acceleration = self.get_peak_acceleration(velocity);
+
+        return velocity / acceleration + acceleration / self.jerk
+    }
+
+    // the ramp is symmetric so it averages half its top speed
+    fn get_ramp_distance(&self, velocity: f64) -> f64 {
+        return velocity * self.get_ramp_time(velocity) / 2.0
+    }
+
+    // short moves have to turn around before max_velocity, bisect for the speed that just fits
+    fn get_peak_velocity(&self, distance: f64) -> f64 {
+        if 2.0 * self.get_ramp_distance(self.max_velocity) <= distance { return self.max_velocity }
+
+        let mut low = 0.0;
+        let mut high = self.max_velocity;
+
+        for _ in 0..60 {
+            let mid = (low + high) / 2.0;
+
+            if 2.0 * self.get_ramp_distance(mid) > distance { high = mid } else { low = mid }
+        }
+
+        return low
+    }
+
+    // velocity t seconds into a ramp up to `peak`
+    fn get_ramp_velocity(&self, peak: f64, t: f64) -> f64 {
+        let acceleration = self.get_peak_acceleration(peak);
+        let jerk_time = acceleration / self.jerk;
+        let ramp_time = self.get_ramp_time(peak);
+
+        if t < jerk_time { return self.jerk * t * t / 2.0 }
+
+        if t > ramp_time - jerk_time { return peak - self.jerk * (ramp_time - t) * (ramp_time - t) / 2.0 }
+
+        return acceleration * (t - jerk_time / 2.0)
+    }
+
+    fn get_velocity(&self, peak: f64, ramp_time: f64, cruise_time: f64, t: f64) -> f64 {
+        if t < ramp_time { return self.get_ramp_velocity(peak, t) }
+
+        if t < ramp_time + cruise_time { return peak }
+
+        return self.get_ramp_velocity(peak, f64::max(0.0, 2.0 * ramp_time + cruise_time - t))
+    }
+
+    // integrates the velocity and notes the time each step boundary gets crossed
+    fn get_delays(&self, steps: u32, step_degree: f32, min_delay: i64) -> Vec<i64> {
+        if steps == 0 { return Vec::new() }
+
+        let steps = steps as usize;
+        let step_degree = step_degree as f64;
+        let distance = steps as f64 * step_degree;
+
+        let peak = self.get_peak_velocity(distance);
+        let ramp_time = self.get_ramp_time(peak);
+        let cruise_time = f64::max(0.0, (distance - 2.0 * self.get_ramp_distance(peak)) / peak);
+        let total_time = 2.0 * ramp_time + cruise_time;
+
+        let dt = total_time / (steps as f64 * 16.0);
+        let mut crossings: Vec<f64> = Vec::new();
+        let mut t = 0.0;
+        let mut position = 0.0;
+        let mut velocity = 0.0;
+
+        while crossings.len() < steps && t < total_time {
+            let next = self.get_velocity(peak, ramp_time, cruise_time, t + dt);
+            let moved = (velocity + next) / 2.0 * dt;
+
+            while crossings.len() < steps && position + moved >= (crossings.len() + 1) as f64 * step_degree {
+                let boundary = (crossings.len() + 1) as f64 * step_degree;
+
+                crossings.push(t + dt * (boundary - position) / moved);
+            }
+
+            position += moved;
+            velocity = next;
+            t += dt;
+        }
+
+        // rounding can leave the last boundary a hair past the end
+        while crossings.len() < steps {
+            crossings.push(total_time);
+        }
+
+        let mut delays: Vec<i64> = Vec::new();
+        let mut last = 0.0;
+
+        for crossing in crossings {
+            delays.push(i64::max(((crossing - last) * 1_000_000.0).round() as i64, min_delay));
+            last = crossing;
+        }
+
+        return delays
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP_DEGREE: f32 = 0.09;
+
+    fn seconds(delays: &[i64]) -> f64 {
+        return delays.iter().sum::<i64>() as f64 / 1_000_000.0
+    }
+
+    #[test]
+    fn ramps_cruise_at_max_velocity() {
+        let profile = Trapezoidal { max_velocity: 30.0, acceleration: 60.0, min_delay: 10 };
+        let delays = profile.get_delays(2000, STEP_DEGREE);
+
+        assert_eq!(delays.len(), 2000);
+
+        // 0.09 degrees at 30 degrees a second
+        let cruise = delays[1000];
+        assert!((cruise - 3000).abs() <= 2, "cruise delay {}", cruise);
+        assert!(delays[0] > delays[40] && delays[40] > cruise);
+        assert!(delays[1999] > delays[1959] && delays[1959] > cruise);
+
+        // 180 degrees, half a second ramping each end and 5.5 seconds cruising
+        assert!((seconds(&delays) - 6.5).abs() < 0.01, "took {}", seconds(&delays));
+    }
+
+    #[test]
+    fn short_moves_never_reach_max_velocity() {
+        let profile = Trapezoidal { max_velocity: 30.0, acceleration: 60.0, min_delay: 10 };
+        let delays = profile.get_delays(40, STEP_DEGREE);
+
+        // 3.6 degrees is a triangle peaking at sqrt(60 * 3.6) degrees a second
+        let fastest = *delays.iter().min().unwrap();
+        assert!(fastest > 3000);
+        assert!((seconds(&delays) - 2.0 * f64::sqrt(3.6 / 60.0)).abs() < 0.005, "took {}", seconds(&delays));
+    }
+
+    #[test]
+    fn s_curve_eases_into_the_ramp() {
+        let trapezoid = Trapezoidal { max_velocity: 30.0, acceleration: 60.0, min_delay: 10 };
+        let s_curve = SCurve { max_velocity: 30.0, acceleration: 60.0, jerk: 240.0, min_delay: 10 };
+
+        let trapezoid = trapezoid.get_delays(2000, STEP_DEGREE);
+        let s_curve = s_curve.get_delays(2000, STEP_DEGREE);
+
+        // limited jerk adds a/j to each ramp, a quarter second over the whole move
+        assert!((seconds(&s_curve) - seconds(&trapezoid) - 0.25).abs() < 0.01);
+        assert!(s_curve[0] > trapezoid[0]);
+        assert!((s_curve[1000] - trapezoid[1000]).abs() <= 2);
+    }
+
+    #[test]
+    fn delays_never_drop_below_the_limit() {
+        let profile = SCurve { max_velocity: 10_000.0, acceleration: 1_000_000.0, jerk: 1e9, min_delay: 50 };
+
+        assert!(profile.get_delays(500, STEP_DEGREE).iter().all(|x| *x >= 50));
+        assert!(profile.get_delays(0, STEP_DEGREE).is_empty());
+    }
+
+    #[test]
+    fn polynomial_keeps_the_old_delays() {
+        let profile = Polynomial { min_delay: 2500, max_delay: 4000 };
+        let old = Calc::normalize_vec(2500, 4000, Calc::smooth((1..=300).collect())).unwrap();
+
+        assert_eq!(profile.get_delays(300, STEP_DEGREE), old);
+        assert_eq!(profile.get_delays(1, STEP_DEGREE), vec![2500]);
+    }
+}
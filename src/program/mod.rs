@@ -0,0 +1,281 @@
+use crate::calc::Calc;
+use crate::driver::{Driver, DriverError};
+use crate::utils::{AngleSet, Utils};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MoveKind {
+    Joint, // every joint straight for its angle
+    Linear, // the tool in a straight line
+    Smooth // like joint, ramped with the configured motion profile
+}
+
+impl MoveKind {
+    pub fn parse(name: &str) -> Option<MoveKind> {
+        match name.to_lowercase().as_str() {
+            "joint" | "j" => Some(MoveKind::Joint),
+            "linear" | "line" | "l" => Some(MoveKind::Linear),
+            "smooth" | "s" => Some(MoveKind::Smooth),
+            _ => None
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            MoveKind::Joint => "joint",
+            MoveKind::Linear => "linear",
+            MoveKind::Smooth => "smooth"
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ProgramError {
+    Unreadable(String, String),
+    Parse(usize, String),
+    Driver(String, DriverError)
+}
+
+impl ProgramError {
+    pub fn get_message(&self) -> String {
+        match self {
+            ProgramError::Unreadable(path, e) => format!("unable to read program {}: {}", path, e),
+            ProgramError::Parse(line, e) => format!("program line {}: {}", line, e),
+            ProgramError::Driver(name, e) => format!("waypoint {}: {}", name, e.get_message())
+        }
+    }
+}
+
+// a taught position. angles are what the joints were at, so replaying lands on the same elbow solution
+#[derive(Clone, PartialEq, Debug)]
+pub struct Waypoint {
+    pub name: String,
+    pub kind: MoveKind,
+    pub speed: f32, // percent of the configured delays, or of motion.tool_speed for linear moves
+    pub angles: AngleSet
+}
+
+impl Waypoint {
+    pub fn execute(&self, driver: &mut Driver) -> Result<(), DriverError> {
+        let scale = self.speed / 100.0;
+
+        match self.kind {
+            MoveKind::Joint => driver.goto_angles(&self.angles, scale),
+
+            MoveKind::Linear => {
+                driver.check_limits(&self.angles)?;
+
+                let target = driver.calc.get_pose(&self.angles).end_effector;
+                driver.goto_line_3d(target.x, target.y, target.z, driver.tool_speed * scale)
+            },
+
+            MoveKind::Smooth => driver.goto_angles_smooth(&self.angles, driver.profile, scale)
+        }
+    }
+
+    // name kind speed column beam base, angles in degrees with the beam absolute like the interface shows them
+    pub fn to_line(&self) -> String {
+        return format!("{} {} {} {} {} {}", self.name, self.kind.get_name(), self.speed, Calc::to_degree(self.angles.column_angle),
+                       Calc::to_degree(self.angles.beam_angle), Calc::to_degree(self.angles.base_angle))
+    }
+
+    pub fn parse(line: usize, text: &str) -> Result<Waypoint, ProgramError> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+
+        if words.len() != 6 {
+            return Err(ProgramError::Parse(line, String::from("expected name kind speed column beam base")))
+        }
+
+        let kind = match MoveKind::parse(words[1]) {
+            Some(x) => x,
+            None => return Err(ProgramError::Parse(line, format!("'{}' is not joint, linear or smooth", words[1])))
+        };
+
+        let mut numbers: Vec<f32> = Vec::new();
+
+        for word in &words[2..] {
+            match word.parse::<f32>() {
+                Ok(x) => numbers.push(x),
+                Err(_) => return Err(ProgramError::Parse(line, format!("'{}' is not a number", word)))
+            }
+        }
+
+        if numbers[0] <= 0.0 || numbers[0] > 100.0 {
+            return Err(ProgramError::Parse(line, format!("speed {} has to be above 0 and at most 100", numbers[0])))
+        }
+
+        let angles = AngleSet { column_angle: Calc::to_radian(numbers[1]), beam_angle: Calc::to_radian(numbers[2]),
+                                base_angle: Calc::to_radian(numbers[3]) };
+
+        return Ok(Waypoint { name: words[0].to_string(), kind, speed: numbers[0], angles })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Program {
+    pub waypoints: Vec<Waypoint>
+}
+
+impl Program {
+    // one waypoint a line, # starts a comment
+    pub fn parse(text: &str) -> Result<Program, ProgramError> {
+        let mut waypoints: Vec<Waypoint> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line_text = line.split('#').next().unwrap_or("").trim();
+
+            if line_text.is_empty() { continue }
+
+            let waypoint = Waypoint::parse(i + 1, line_text)?;
+
+            if waypoints.iter().any(|x| x.name == waypoint.name) {
+                return Err(ProgramError::Parse(i + 1, format!("there's already a waypoint called {}", waypoint.name)))
+            }
+
+            waypoints.push(waypoint);
+        }
+
+        return Ok(Program { waypoints })
+    }
+
+    pub fn load(path: &str) -> Result<Program, ProgramError> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(x) => x,
+            Err(e) => return Err(ProgramError::Unreadable(path.to_string(), format!("{}", e)))
+        };
+
+        return Program::parse(&text)
+    }
+
+    pub fn save(&self, path: &str) -> Result<String, std::io::Error> {
+        return Utils::save_to_file(path.to_string(), self.to_text())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# name kind speed column beam base\n");
+
+        for waypoint in &self.waypoints {
+            text.push_str(&waypoint.to_line());
+            text.push('\n');
+        }
+
+        return text
+    }
+
+    // replay and completion go by name, so every waypoint needs its own
+    pub fn has_waypoint(&self, name: &str) -> bool {
+        return self.waypoints.iter().any(|x| x.name == name)
+    }
+
+    // the operator's name unless it's empty, then the next free p<n>
+    pub fn get_free_name(&self, name: &str) -> String {
+        if !name.is_empty() { return name.to_string() }
+
+        let mut i = self.waypoints.len() + 1;
+
+        while self.waypoints.iter().any(|x| x.name == format!("p{}", i)) {
+            i += 1;
+        }
+
+        return format!("p{}", i)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ReplayState {
+    Running,
+    Paused, // also where step through sits between waypoints
+    Done
+}
+
+// walks a program one waypoint at a time so the caller can pause or stop between them
+pub struct Replay {
+    pub program: Program,
+    pub next: usize,
+    pub loops: u32, // 0 keeps going until stopped
+    pub finished_loops: u32,
+    pub state: ReplayState
+}
+
+impl Replay {
+    pub fn new(program: Program, loops: u32) -> Replay {
+        let state = if program.waypoints.is_empty() { ReplayState::Done } else { ReplayState::Running };
+
+        return Replay { program, next: 0, loops, finished_loops: 0, state }
+    }
+
+    // runs the next waypoint whatever the state, a failed move stops the replay where it is
+    pub fn step(&mut self, driver: &mut Driver) -> Result<String, ProgramError> {
+        let waypoint = match self.get_next() {
+            Some(x) => x,
+            None => return Ok(String::from("nothing left to replay"))
+        };
+
+        if let Err(e) = waypoint.execute(driver) {
+            self.advance(false);
+            return Err(ProgramError::Driver(waypoint.name.clone(), e))
+        }
+
+        self.advance(true);
+
+        return Ok(format!("{} {} at {}%", waypoint.kind.get_name(), waypoint.name, waypoint.speed))
+    }
+
+    // the waypoint step would run, for callers that run it somewhere else and report back with advance
+    pub fn get_next(&mut self) -> Option<Waypoint> {
+        let waypoint = self.program.waypoints.get(self.next).cloned();
+
+        if waypoint.is_none() { self.state = ReplayState::Done }
+
+        return waypoint
+    }
+
+    pub fn advance(&mut self, ok: bool) {
+        if !ok {
+            self.state = ReplayState::Paused;
+            return
+        }
+
+        self.next += 1;
+
+        if self.next == self.program.waypoints.len() {
+            self.next = 0;
+            self.finished_loops += 1;
+
+            if self.loops != 0 && self.finished_loops >= self.loops { self.state = ReplayState::Done }
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.state = match self.state {
+            ReplayState::Running => ReplayState::Paused,
+            ReplayState::Paused => ReplayState::Running,
+            ReplayState::Done => ReplayState::Done
+        }
+    }
+
+    // runs whatever is left without stopping, for headless use
+    pub fn run(&mut self, driver: &mut Driver) -> Result<usize, ProgramError> {
+        let mut count = 0;
+
+        while self.state != ReplayState::Done {
+            self.step(driver)?;
+            count += 1;
+        }
+
+        return Ok(count)
+    }
+
+    pub fn get_status(&self) -> String {
+        let loops = if self.loops == 0 { String::from("forever") } else { format!("{}", self.loops) };
+        let state = match self.state {
+            ReplayState::Running => "running",
+            ReplayState::Paused => "paused",
+            ReplayState::Done => return format!("done after {} loops", self.finished_loops)
+        };
+
+        return format!("{} {}/{} loop {}/{}", state, self.next + 1, self.program.waypoints.len(), self.finished_loops + 1, loops)
+    }
+}
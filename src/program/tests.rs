@@ -0,0 +1,81 @@
+use super::*;
+use crate::config::Config;
+use crate::driver::{Backend, Joint};
+
+fn test_driver() -> Driver {
+    let mut driver = Driver::new(Backend::Simulated, &Config::default()).unwrap();
+    driver.micro_delay_default = 10;
+    driver.micro_delay_limit = 10;
+    driver.micro_delay_min = 10;
+    driver.micro_delay_max = 20;
+    driver.max_velocity = 100_000.0;
+    driver.tool_speed = 50.0;
+
+    return driver
+}
+
+fn waypoint(name: &str, kind: MoveKind, column: f32, beam: f32, base: f32) -> Waypoint {
+    let angles = AngleSet { column_angle: Calc::to_radian(column), beam_angle: Calc::to_radian(beam), base_angle: Calc::to_radian(base) };
+
+    return Waypoint { name: name.to_string(), kind, speed: 100.0, angles }
+}
+
+#[test]
+fn programs_survive_a_round_trip_through_text() {
+    let program = Program { waypoints: vec![waypoint("pick", MoveKind::Joint, 45.0, 10.0, 0.0),
+                                            waypoint("place", MoveKind::Linear, 60.0, -20.0, 15.0)] };
+    let parsed = Program::parse(&program.to_text()).unwrap();
+
+    assert_eq!(parsed.waypoints.len(), 2);
+    assert_eq!(parsed.waypoints[1].name, "place");
+    assert_eq!(parsed.waypoints[1].kind, MoveKind::Linear);
+    assert!((parsed.waypoints[1].angles.base_angle - Calc::to_radian(15.0)).abs() < 1e-5);
+
+    assert!(matches!(Program::parse("a joint 100 1 2"), Err(ProgramError::Parse(1, _))));
+    assert!(matches!(Program::parse("# header\na jump 100 1 2 3"), Err(ProgramError::Parse(2, _))));
+    assert!(matches!(Program::parse("a joint 0 1 2 3"), Err(ProgramError::Parse(1, _))));
+    assert!(matches!(Program::parse("a joint 100 1 2 3\nb joint 100 1 2 3\na linear 50 4 5 6"), Err(ProgramError::Parse(3, _))));
+    assert!(program.has_waypoint("pick") && !program.has_waypoint("p3"));
+    assert_eq!(program.get_free_name(""), "p3");
+}
+
+#[test]
+fn replays_land_on_the_taught_angles() {
+    let mut driver = test_driver();
+    let program = Program { waypoints: vec![waypoint("a", MoveKind::Joint, 45.0, 10.0, 5.0),
+                                            waypoint("b", MoveKind::Smooth, 60.0, -20.0, 0.0),
+                                            waypoint("c", MoveKind::Linear, 50.0, 0.0, 10.0)] };
+    let mut replay = Replay::new(program, 2);
+
+    replay.step(&mut driver).unwrap();
+    assert_eq!(driver.get_step_count(Joint::Column), driver.get_target_steps(Joint::Column, 45.0));
+    assert_eq!(driver.get_step_count(Joint::Base), driver.get_target_steps(Joint::Base, 5.0));
+
+    replay.step(&mut driver).unwrap();
+    assert_eq!(driver.get_step_count(Joint::Beam), driver.get_target_steps(Joint::Beam, -80.0));
+
+    // two loops of three, with the one already run
+    assert_eq!(replay.run(&mut driver).unwrap(), 4);
+    assert_eq!(replay.state, ReplayState::Done);
+    assert_eq!(replay.finished_loops, 2);
+
+    let target = driver.calc.get_pose(&replay.program.waypoints[2].angles).end_effector;
+    assert!(Calc::dist_3d(&driver.current_position, &target) < 5e-3);
+}
+
+#[test]
+fn failed_waypoints_pause_the_replay() {
+    let mut driver = test_driver();
+    let program = Program { waypoints: vec![waypoint("a", MoveKind::Joint, 45.0, 10.0, 0.0),
+                                            waypoint("far", MoveKind::Joint, 45.0, 10.0, 400.0)] };
+    let mut replay = Replay::new(program, 1);
+
+    replay.step(&mut driver).unwrap();
+
+    assert!(matches!(replay.step(&mut driver), Err(ProgramError::Driver(_, DriverError::OutOfLimits(Joint::Base, _, _)))));
+    assert_eq!(replay.state, ReplayState::Paused);
+    assert_eq!(replay.next, 1);
+
+    replay.toggle_pause();
+    assert_eq!(replay.state, ReplayState::Running);
+}
@@ -1,27 +1,48 @@
 use rppal::gpio::{OutputPin, Gpio};
 
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub trait Motor: Send {
+    fn set_direction(&mut self, direction: bool);
+
+    fn step(&mut self, direction: bool);
+
+    fn reset(&mut self); // must call reset before calling step() again
+
+    fn get_direction_pin(&self) -> u8;
+
+    fn get_step_pin(&self) -> u8;
+
+    fn is_simulated(&self) -> bool;
+
+    fn get_recorder(&self) -> Option<StepRecorder> {
+        return None
+    }
+
+    fn describe(&self) -> String {
+        let kind = if self.is_simulated() { "sim" } else { "gpio" };
+
+        return format!("{} {}/{}", kind, self.get_direction_pin(), self.get_step_pin())
+    }
+}
+
 pub struct Stepper {
     direction_pin: u8,
     step_pin: u8,
+    invert: bool,
     dir: OutputPin,
     step: OutputPin,
 }
 
 impl Stepper {
-    pub fn new(direction_pin: u8, step_pin: u8) -> Self {
-        let dir = Gpio::new().unwrap().get(direction_pin).unwrap().into_output();
-        let step = Gpio::new().unwrap().get(step_pin).unwrap().into_output();
-        Self { direction_pin, step_pin, dir, step }
-    }
+    pub fn new(direction_pin: u8, step_pin: u8, invert: bool) -> Result<Self, rppal::gpio::Error> {
+        let gpio = Gpio::new()?;
+        let dir = gpio.get(direction_pin)?.into_output();
+        let step = gpio.get(step_pin)?.into_output();
 
-    pub fn step(&mut self, direction: bool) {
-        if direction { self.dir.set_high() }
-        else { self.dir.set_low() }
-        self.step.set_high();
-    }
-
-    pub fn reset(&mut self) { // must call reset before calling step() again
-        self.step.set_low();
+        Ok(Self { direction_pin, step_pin, invert, dir, step })
     }
 
     // pub fn step(&mut self, steps: usize, direction: bool) { // direction == true CW || direction == false CCW
@@ -33,25 +54,204 @@ impl Stepper {
     //         self.step.set_low();
     //     }
     // }
+}
+
+impl Motor for Stepper {
+    fn set_direction(&mut self, direction: bool) {
+        if direction != self.invert { self.dir.set_high() }
+        else { self.dir.set_low() }
+    }
+
+    fn step(&mut self, direction: bool) {
+        self.set_direction(direction);
+        self.step.set_high();
+    }
+
+    fn reset(&mut self) {
+        self.step.set_low();
+    }
+
+    fn get_direction_pin(&self) -> u8 {
+        return self.direction_pin
+    }
+
+    fn get_step_pin(&self) -> u8 {
+        return self.step_pin
+    }
+
+    fn is_simulated(&self) -> bool {
+        return false
+    }
+}
+
+pub type StepRecorder = Arc<Mutex<StepTrace>>;
+
+// a simulated motor lives as long as the interface does, so only this many of the newest events are
+// kept. a step and its reset are two, enough for a few long moves
+pub const TRACE_CAPACITY: usize = 100_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceKind {
+    Direction(bool),
+    Step,
+    Reset
+}
+
+#[derive(Clone, Debug)]
+pub struct TraceEvent {
+    pub kind: TraceKind,
+    pub at: Duration,
+    pub interval: Option<Duration>,
+    pub position: i64
+}
+
+// what a simulated motor saw on its pins lately. position counts +1 for every step edge
+// while the direction pin is high and -1 while it is low, and keeps counting past the capacity
+pub struct StepTrace {
+    start: Instant,
+    events: VecDeque<TraceEvent>,
+    position: i64,
+    direction: Option<bool>,
+    step_high: bool,
+    last_step: Option<Instant>
+}
 
+impl StepTrace {
+    pub fn new() -> StepTrace {
+        return StepTrace { start: Instant::now(), events: VecDeque::new(), position: 0, direction: None,
+                           step_high: false, last_step: None }
+    }
+
+    fn record(&mut self, kind: TraceKind, now: Instant, interval: Option<Duration>) {
+        let at = now.duration_since(self.start);
+        let position = self.position;
+
+        if self.events.len() == TRACE_CAPACITY { self.events.pop_front(); }
+
+        self.events.push_back(TraceEvent { kind, at, interval, position });
+    }
+
+    pub fn set_direction(&mut self, direction: bool) {
+        if self.direction == Some(direction) { return }
+
+        self.direction = Some(direction);
+        self.record(TraceKind::Direction(direction), Instant::now(), None);
+    }
+
+    pub fn step(&mut self) {
+        if self.step_high { return } // no edge without a reset in between
+
+        let now = Instant::now();
+        let interval = self.last_step.map(|last| now.duration_since(last));
+
+        self.position += if self.direction == Some(true) { 1 } else { -1 };
+        self.step_high = true;
+        self.last_step = Some(now);
+        self.record(TraceKind::Step, now, interval);
+    }
+
+    pub fn reset(&mut self) {
+        if !self.step_high { return }
+
+        self.step_high = false;
+        self.record(TraceKind::Reset, Instant::now(), None);
+    }
+
+    pub fn get_position(&self) -> i64 {
+        return self.position
+    }
+
+    #[cfg(test)]
+    pub fn get_step_count(&self) -> usize {
+        return self.events.iter().filter(|x| x.kind == TraceKind::Step).count()
+    }
+
+    #[cfg(test)]
+    pub fn get_direction_changes(&self) -> Vec<bool> {
+        return self.events.iter().filter_map(|x| match x.kind {
+            TraceKind::Direction(dir) => Some(dir),
+            _ => None
+        }).collect()
+    }
+
+    #[cfg(test)]
+    pub fn get_step_intervals(&self) -> Vec<Duration> {
+        return self.events.iter().filter_map(|x| x.interval).collect()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time_us,event,interval_us,position\n");
+
+        for event in &self.events {
+            let kind = match event.kind {
+                TraceKind::Direction(true) => "dir_high",
+                TraceKind::Direction(false) => "dir_low",
+                TraceKind::Step => "step",
+                TraceKind::Reset => "reset"
+            };
+
+            let interval = match event.interval {
+                Some(x) => format!("{}", x.as_micros()),
+                None => String::new()
+            };
+
+            csv.push_str(&format!("{},{},{},{}\n", event.at.as_micros(), kind, interval, event.position));
+        }
+
+        return csv
+    }
 }
 
 pub struct TestStepper {
     direction_pin: u8,
     step_pin: u8,
+    invert: bool,
+    trace: StepRecorder,
 }
 
 impl TestStepper {
     pub fn new(direction_pin: u8, step_pin: u8) -> Self {
-        Self { direction_pin, step_pin}
+        let trace = Arc::new(Mutex::new(StepTrace::new()));
+
+        Self { direction_pin, step_pin, invert: false, trace }
+    }
+
+    pub fn inverted(mut self, invert: bool) -> Self {
+        self.invert = invert;
+
+        return self
+    }
+}
+
+impl Motor for TestStepper {
+    fn set_direction(&mut self, direction: bool) {
+        self.trace.lock().unwrap().set_direction(direction != self.invert);
+    }
+
+    fn step(&mut self, direction: bool) {
+        let mut trace = self.trace.lock().unwrap();
+
+        trace.set_direction(direction != self.invert);
+        trace.step();
+    }
+
+    fn reset(&mut self) {
+        self.trace.lock().unwrap().reset();
+    }
+
+    fn get_direction_pin(&self) -> u8 {
+        return self.direction_pin
+    }
+
+    fn get_step_pin(&self) -> u8 {
+        return self.step_pin
     }
 
-    pub fn step(&mut self, direction: bool) {
-        if direction {  }
-        else {  };
+    fn is_simulated(&self) -> bool {
+        return true
     }
 
-    pub fn reset(&mut self) { // must call reset before calling step() again
-        
+    fn get_recorder(&self) -> Option<StepRecorder> {
+        return Some(Arc::clone(&self.trace))
     }
 }
@@ -0,0 +1,53 @@
+use rppal::gpio::{InputPin, Gpio};
+
+use crate::stepper::StepRecorder;
+
+pub trait Switch: Send {
+    fn is_triggered(&self) -> bool;
+}
+
+pub struct LimitSwitch {
+    active_low: bool,
+    input: InputPin
+}
+
+impl LimitSwitch {
+    // active low switches short the pin to ground, so they get the internal pull up
+    pub fn new(pin: u8, active_low: bool) -> Result<Self, rppal::gpio::Error> {
+        let gpio = Gpio::new()?;
+        let input = if active_low { gpio.get(pin)?.into_input_pullup() } else { gpio.get(pin)?.into_input_pulldown() };
+
+        Ok(Self { active_low, input })
+    }
+}
+
+impl Switch for LimitSwitch {
+    fn is_triggered(&self) -> bool {
+        return self.input.is_low() == self.active_low
+    }
+}
+
+// closes once the simulated motor's pin position reaches trigger_position, coming from either side
+pub struct TestSwitch {
+    trace: StepRecorder,
+    trigger_position: i64,
+    toward_positive: bool
+}
+
+impl TestSwitch {
+    pub fn new(trace: StepRecorder, trigger_position: i64, toward_positive: bool) -> Self {
+        Self { trace, trigger_position, toward_positive }
+    }
+}
+
+impl Switch for TestSwitch {
+    fn is_triggered(&self) -> bool {
+        let position = self.trace.lock().unwrap().get_position();
+
+        if self.toward_positive {
+            return position >= self.trigger_position
+        }
+
+        return position <= self.trigger_position
+    }
+}
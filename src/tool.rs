@@ -0,0 +1,59 @@
+use rppal::gpio::{OutputPin, Gpio};
+
+// whatever is on the end of the arm, a gripper, pump or pen lift switched by one output pin
+pub trait Tool: Send {
+    fn set(&mut self, on: bool);
+
+    fn is_on(&self) -> bool;
+}
+
+pub struct ToolOutput {
+    active_low: bool,
+    on: bool,
+    output: OutputPin
+}
+
+impl ToolOutput {
+    // starts off, which is high for active low tools
+    pub fn new(pin: u8, active_low: bool) -> Result<Self, rppal::gpio::Error> {
+        let mut output = Gpio::new()?.get(pin)?.into_output();
+
+        if active_low { output.set_high() } else { output.set_low() }
+
+        Ok(Self { active_low, on: false, output })
+    }
+}
+
+impl Tool for ToolOutput {
+    fn set(&mut self, on: bool) {
+        if on != self.active_low { self.output.set_high() }
+        else { self.output.set_low() }
+
+        self.on = on;
+    }
+
+    fn is_on(&self) -> bool {
+        return self.on
+    }
+}
+
+#[derive(Default)]
+pub struct TestTool {
+    on: bool
+}
+
+impl TestTool {
+    pub fn new() -> Self {
+        Self { on: false }
+    }
+}
+
+impl Tool for TestTool {
+    fn set(&mut self, on: bool) {
+        self.on = on;
+    }
+
+    fn is_on(&self) -> bool {
+        return self.on
+    }
+}
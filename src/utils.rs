@@ -11,13 +11,20 @@ pub struct Point {
     pub z: f32
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct AngleSet {
     pub column_angle: f32,
     pub beam_angle: f32,
     pub base_angle: f32
 }
 
+#[derive(Clone)]
+pub struct ArmPose {
+    pub base: Point,
+    pub elbow: Point,
+    pub end_effector: Point
+}
+
 pub struct ShiftingVec<T> where T: Clone {
     state: ListState,
     items: Vec<T>,
@@ -68,12 +75,6 @@ impl<T> ShiftingVec<T> where T: Clone {
         }
     }
 
-    pub fn set_all(&mut self, item: T) {
-        for _ in 0..self.items.len() {
-            self.insert(item.clone());
-        }
-    }
-
     pub fn get_items(&self) -> &Vec<T> {
         return &self.items;
     }
@@ -87,12 +88,9 @@ pub struct Utils;
 
 impl Utils {
     pub fn save_to_file(path: String, contents: String) -> Result<String, std::io::Error>{
-        let mut file = match File::create(format!("{}", path)) {
-            Ok(x) => x,
-            Err(e) => return Err(e)
-        };
+        let mut file = File::create(&path)?;
 
-        match file.write(contents.as_bytes()) {
+        match file.write_all(contents.as_bytes()) {
             Ok(_) => return Ok(format!("successfully saved to {}", path)),
             Err(e) => return Err(e)
         }